@@ -0,0 +1,82 @@
+//! Adobe RGB (1998), the wide gamut space common in photo workflows
+
+use channel::Channel;
+use color_space::{D50, D65, Mat3, MatrixColorSpace, TransferFunction};
+use num_traits::{Float, cast};
+use yxy::Yxy;
+
+/// Adobe RGB (1998) standard, use as `Rgb<T, AdobeRgb>`.
+/// See http://www.brucelindbloom.com/index.html?Eqn_RGB_XYZ_Matrix.html
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdobeRgb;
+
+impl MatrixColorSpace for AdobeRgb {
+    type WhitePoint = D65;
+
+    fn red<T: Channel + Float>() -> Yxy<T, D50> {
+        Yxy::new(0.6400.to_channel(), 0.3300.to_channel(), 0.297361.to_channel())
+    }
+    fn green<T: Channel + Float>() -> Yxy<T, D50> {
+        Yxy::new(0.2100.to_channel(), 0.7100.to_channel(), 0.627355.to_channel())
+    }
+    fn blue<T: Channel + Float>() -> Yxy<T, D50> {
+        Yxy::new(0.1500.to_channel(), 0.0600.to_channel(), 0.075285.to_channel())
+    }
+    fn to_xyz_matrix<T: Channel + Float>() -> Mat3<T>{
+        Mat3([
+            0.5767309.to_channel(),  0.1855540.to_channel(),  0.1881852.to_channel(),
+            0.2973769.to_channel(),  0.6273491.to_channel(),  0.0752741.to_channel(),
+            0.0270343.to_channel(),  0.0706872.to_channel(),  0.9911085.to_channel(),
+        ])
+    }
+    fn to_rgb_matrix<T: Channel + Float>() -> Mat3<T>{
+        Mat3([
+            2.0413690.to_channel(), (-0.5649464).to_channel(), (-0.3446944).to_channel(),
+            (-0.9692660).to_channel(),  1.8760108.to_channel(),  0.0415560.to_channel(),
+            0.0134474.to_channel(), (-0.1183897).to_channel(),  1.0154096.to_channel(),
+        ])
+    }
+}
+
+/// Pure 563/256 gamma without a linear segment, negative values mirror the
+/// curve.
+impl TransferFunction for AdobeRgb {
+    fn from_linear<T: Float>(x: T) -> T {
+        x.signum() * x.abs().powf(cast(256. / 563.).unwrap())
+    }
+
+    fn to_linear<T: Float>(x: T) -> T {
+        x.signum() * x.abs().powf(cast(563. / 256.).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AdobeRgb;
+    use color_space::{D65, TransferFunction};
+    use rgb::Rgb;
+    use xyz::{Xyz, ToXyz};
+
+    #[test]
+    fn test_primaries() {
+        let red = Rgb::<f64, AdobeRgb>::new(1., 0., 0.).to_xyz::<f64>();
+        assert!((red.x - 0.5767309).abs() < 1e-7 && (red.y - 0.2973769).abs() < 1e-7 && (red.z - 0.0270343).abs() < 1e-7, "{:?}", red);
+        let white = Rgb::<f64, AdobeRgb>::new(1., 1., 1.).to_xyz::<f64>();
+        assert!((white.x - 0.95047).abs() < 1e-4 && (white.y - 1.).abs() < 1e-4 && (white.z - 1.08883).abs() < 1e-4, "{:?}", white);
+    }
+
+    #[test]
+    fn test_transfer_function() {
+        assert!((AdobeRgb::to_linear(0.5f64) - 0.5f64.powf(2.19921875)).abs() < 1e-12);
+        assert!((AdobeRgb::from_linear(AdobeRgb::to_linear(0.3f64)) - 0.3).abs() < 1e-12);
+        assert_eq!(AdobeRgb::to_linear(0f64), 0.);
+    }
+
+    #[test]
+    fn test_xyz_round_trip() {
+        let rgb = Rgb::<f64, AdobeRgb>::new(0.2, 0.5, 0.8);
+        let xyz: Xyz<f64, D65> = rgb.to_xyz();
+        let back = Rgb::<f64, AdobeRgb>::from_xyz(&xyz);
+        assert!((back.r - rgb.r).abs() < 1e-6 && (back.g - rgb.g).abs() < 1e-6 && (back.b - rgb.b).abs() < 1e-6, "{:?}", back);
+    }
+}
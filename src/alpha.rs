@@ -21,6 +21,8 @@ use {Color, Channel};
 use {Rgb, Rg, ToRgb, Hsv, YCbCr};
 use color_space::{Srgb, LinearRgb};
 use luma::{Luma, ToLuma};
+use error::ParseError;
+use rgb::parse_hex_channels;
 
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub struct AlphaColor<T, C> { pub c: C, pub a: T }
@@ -73,6 +75,16 @@ impl<T: Channel, C: Color<T>> Color<T> for AlphaColor<T, C> {
     }
 }
 
+impl<S> Rgba<u8, S> {
+    /// Parses a `#rrggbbaa` hex string or any form `Rgb::from_hex_str`
+    /// accepts, plus the short `#rgba`. Colors without an alpha digit are
+    /// opaque.
+    pub fn from_hex_str(hex: &str) -> Result<Rgba<u8, S>, ParseError> {
+        let [r, g, b, a] = parse_hex_channels(hex, true)?;
+        Ok(Rgba::new(Rgb::new(r, g, b), a))
+    }
+}
+
 #[macro_export]
 macro_rules! rgba{
     ( $r: expr, $g: expr, $b: expr, $a: expr ) => ({
@@ -407,6 +419,64 @@ impl<T:Channel, S: TransferFunction> Rgba<T, S> {
     }
 }
 
+impl<T: Channel, S> Rgba<T, S> {
+    /// Opaque color with all color channels set to `v`.
+    #[inline]
+    pub fn splat(v: T) -> Rgba<T, S> {
+        Rgba::splat_with_alpha(v, T::max())
+    }
+
+    /// Color with all color channels set to `v` and alpha `a`.
+    #[inline]
+    pub fn splat_with_alpha(v: T, a: T) -> Rgba<T, S> {
+        Rgba::new(Rgb::splat(v), a)
+    }
+}
+
+/// How `Rgba::blend` combines a layer with its backdrop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Source over with the product of both colors, always darker.
+    Multiply,
+    /// Source over with the inverse of the product of the inverses, always
+    /// lighter.
+    Screen,
+    /// Multiplies dark backdrops and screens light ones, increasing contrast.
+    Overlay,
+    /// Source over with the darkest of both colors per channel.
+    Darken,
+    /// Source over with the lightest of both colors per channel.
+    Lighten,
+    /// Porter-Duff source over, the layer on top of the backdrop.
+    SrcOver,
+    /// Porter-Duff destination over, the backdrop on top of the layer.
+    DstOver,
+    /// Porter-Duff source in, the layer where the backdrop is.
+    SrcIn,
+    /// Porter-Duff destination out, the backdrop where the layer isn't.
+    DstOut,
+}
+
+impl BlendMode {
+    // Mixes the backdrop and source channels where both overlap
+    fn mix(self, c_b: f64, c_s: f64) -> f64 {
+        let multiply = |a: f64, b: f64| a * b;
+        let screen = |a: f64, b: f64| a + b - a * b;
+        match self {
+            BlendMode::Multiply => multiply(c_b, c_s),
+            BlendMode::Screen => screen(c_b, c_s),
+            BlendMode::Overlay => if c_b <= 0.5 {
+                multiply(c_s, 2. * c_b)
+            }else{
+                screen(c_s, 2. * c_b - 1.)
+            },
+            BlendMode::Darken => c_b.min(c_s),
+            BlendMode::Lighten => c_b.max(c_s),
+            BlendMode::SrcOver | BlendMode::DstOver | BlendMode::SrcIn | BlendMode::DstOut => c_s,
+        }
+    }
+}
+
 impl<T: Channel, S: TransferFunction> Rgba<T, S> {
     pub fn to_standard<S2: TransferFunction>(&self) -> Rgba<T, S2>{
         let c = self.c.to_standard();
@@ -417,8 +487,148 @@ impl<T: Channel, S: TransferFunction> Rgba<T, S> {
         let c = self.c.to_linear();
         Rgba{c, a: self.a}
     }
+
+    // Composites over an opaque background given in linear light
+    fn flatten_on_linear(&self, background: [f64; 3]) -> Rgb<T, S> {
+        let a = self.a.to_channel_f64();
+        let blend = |c: T, bg: f64| {
+            let c = S::to_linear(c.to_channel_f64()) * a + bg * (1. - a);
+            S::from_linear(c).to_channel()
+        };
+        Rgb::new(
+            blend(self.c.r, background[0]),
+            blend(self.c.g, background[1]),
+            blend(self.c.b, background[2]),
+        )
+    }
+
+    /// Composites the color over `background` with premultiplied source over
+    /// in linear light, the result is as opaque as both layers together.
+    pub fn over(&self, background: &Rgba<T, S>) -> Rgba<T, S> {
+        self.blend(background, BlendMode::SrcOver)
+    }
+
+    /// Composites the color onto `backdrop` with `mode`, following the W3C
+    /// compositing spec on linear, premultiplied values. Blend modes mix the
+    /// colors where both layers overlap and composite the result with source
+    /// over.
+    pub fn blend(&self, backdrop: &Rgba<T, S>, mode: BlendMode) -> Rgba<T, S> {
+        let a_s = self.a.to_channel_f64();
+        let a_b = backdrop.a.to_channel_f64();
+        // Porter-Duff fractions of the source and the backdrop that remain
+        let (f_s, f_b) = match mode {
+            BlendMode::DstOver => (1. - a_b, 1.),
+            BlendMode::SrcIn => (a_b, 0.),
+            BlendMode::DstOut => (0., 1. - a_s),
+            _ => (1., 1. - a_s),
+        };
+        let a = a_s * f_s + a_b * f_b;
+        if a == 0. {
+            return Rgba::new(Rgb::splat(T::zero()), T::zero());
+        }
+        let composite = |src: T, dst: T| {
+            let c_s = S::to_linear(src.to_channel_f64());
+            let c_b = S::to_linear(dst.to_channel_f64());
+            let c_s = (1. - a_b) * c_s + a_b * mode.mix(c_b, c_s);
+            S::from_linear((c_s * a_s * f_s + c_b * a_b * f_b) / a).to_channel()
+        };
+        Rgba::new(Rgb::new(
+            composite(self.c.r, backdrop.c.r),
+            composite(self.c.g, backdrop.c.g),
+            composite(self.c.b, backdrop.c.b),
+        ), a.to_channel())
+    }
+
+    /// Composites the color over the opaque `background` in linear light.
+    pub fn flatten_over(&self, background: &Rgb<T, S>) -> Rgb<T, S> {
+        let linear = |c: T| S::to_linear(c.to_channel_f64());
+        self.flatten_on_linear([linear(background.r), linear(background.g), linear(background.b)])
+    }
+
+    /// Composites the color over solid white in linear light.
+    pub fn flatten_on_white(&self) -> Rgb<T, S> {
+        self.flatten_on_linear([1., 1., 1.])
+    }
+
+    /// Composites the color over solid black in linear light.
+    pub fn flatten_on_black(&self) -> Rgb<T, S> {
+        self.flatten_on_linear([0., 0., 0.])
+    }
 }
 
+impl Rgba<u8, Srgb> {
+    /// Renders pixel `(x, y)` of the color over a checkerboard of `cell` sized
+    /// squares, starting with a light one at the origin, compositing in
+    /// linear light. Useful to preview transparency in swatches.
+    pub fn composite_on_checker(&self, x: u32, y: u32, cell: u32, (light, dark): (Rgb<u8, Srgb>, Rgb<u8, Srgb>)) -> Rgb<u8, Srgb> {
+        assert!(cell > 0, "Checkerboard cells can't be empty");
+        let background = if (x / cell + y / cell) & 1 == 0 { light } else { dark };
+        let linear = |c: u8| Srgb::to_linear(c.to_channel_f64());
+        self.flatten_on_linear([linear(background.r), linear(background.g), linear(background.b)])
+    }
+}
+
+impl Rgba<f32, Srgb> {
+    /// Interpolates in premultiplied linear light and unpremultiplies the
+    /// result, so a color fading out doesn't bleed its hue into the other
+    /// one. The way to tween transparent colors, `t` is clamped to `[0, 1]`.
+    pub fn lerp(&self, other: &Rgba<f32, Srgb>, t: f32) -> Rgba<f32, Srgb> {
+        let t = t.clamp(0., 1.);
+        if t == 0. {
+            return *self;
+        }else if t == 1. {
+            return *other;
+        }
+        let a = self.a + (other.a - self.a) * t;
+        if a == 0. {
+            return Rgba::new(Rgb::new(0., 0., 0.), 0.);
+        }
+        let blend = |c1: f32, c2: f32| {
+            let c = Srgb::to_linear(c1) * self.a * (1. - t) + Srgb::to_linear(c2) * other.a * t;
+            Srgb::from_linear(c / a)
+        };
+        Rgba::new(Rgb::new(
+            blend(self.c.r, other.c.r),
+            blend(self.c.g, other.c.g),
+            blend(self.c.b, other.c.b),
+        ), a)
+    }
+}
+
+/// Composites `layers`, ordered back to front, with source over in linear
+/// premultiplied light and fades the result by the `group_alpha` opacity.
+/// Transparent black for an empty stack.
+pub fn composite_stack(layers: &[Rgba<f32, Srgb>], group_alpha: f32) -> Rgba<f32, Srgb> {
+    let mut acc = [0f32; 3];
+    let mut acc_a = 0f32;
+    for layer in layers {
+        let a = layer.a;
+        let c = [layer.c.r, layer.c.g, layer.c.b];
+        for (acc, c) in acc.iter_mut().zip(c.iter()) {
+            *acc = Srgb::to_linear(*c) * a + *acc * (1. - a);
+        }
+        acc_a = a + acc_a * (1. - a);
+    }
+    if acc_a == 0. {
+        return Rgba::new(Rgb::new(0., 0., 0.), 0.);
+    }
+    let encode = |c: f32| Srgb::from_linear(c / acc_a);
+    Rgba::new(Rgb::new(encode(acc[0]), encode(acc[1]), encode(acc[2])), acc_a * group_alpha)
+}
+
+impl<S> Rgba<u8, S> {
+    /// Packs the color as RGBA8.
+    #[inline]
+    pub fn to_rgba8(&self) -> [u8; 4] {
+        [self.c.r, self.c.g, self.c.b, self.a]
+    }
+
+    /// Unpacks an RGBA8 color.
+    #[inline]
+    pub fn from_rgba8(rgba: [u8; 4]) -> Rgba<u8, S> {
+        Rgba::new(Rgb::new(rgba[0], rgba[1], rgba[2]), rgba[3])
+    }
+}
 
 pub trait ToRgba{
     type Standard: TransferFunction;
@@ -559,3 +769,179 @@ impl<T, C: BorrowMut<[T;3]>> BorrowMut<[T;4]> for AlphaColor<T,C> {
         unsafe{ mem::transmute(self)}
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use {Rgb, Rgba, ToRgb};
+    use super::{composite_stack, BlendMode};
+    use color_space::{Srgb, LinearRgb, TransferFunction};
+    use half::f16;
+
+    #[test]
+    fn test_from_hex_str() {
+        use error::ParseError;
+        assert_eq!(Rgba::<u8>::from_hex_str("#12345678"), Ok(rgba!(0x12, 0x34, 0x56, 0x78)));
+        assert_eq!(Rgba::<u8>::from_hex_str("#1234"), Ok(rgba!(0x11, 0x22, 0x33, 0x44)));
+        assert_eq!(Rgba::<u8>::from_hex_str("123456"), Ok(rgba!(0x12, 0x34, 0x56, 0xFF)));
+        assert_eq!(Rgba::<u8>::from_hex_str("#fff"), Ok(rgba!(0xFF, 0xFF, 0xFF, 0xFF)));
+        assert_eq!(Rgba::<u8>::from_hex_str("#1234567"), Err(ParseError::InvalidLength(7)));
+        assert_eq!(Rgba::<u8>::from_hex_str("#1234567g"), Err(ParseError::InvalidDigit('g')));
+    }
+
+    #[test]
+    fn test_flatten() {
+        let half_black = rgba!(0f32, 0., 0., 0.5);
+        assert_eq!(half_black.flatten_on_white().to_rgb::<u8>(), rgb!(188, 188, 188));
+        assert_eq!(half_black.flatten_on_black(), rgb!(0., 0., 0.));
+        // 0x80 is slightly more than half opaque
+        assert_eq!(rgba!(0u8, 0, 0, 0x80).flatten_on_white(), rgb!(187, 187, 187));
+        let half_white = rgba!(1f32, 1., 1., 0.5);
+        assert!((half_white.flatten_on_white().r - 1.).abs() < 1e-6);
+        let opaque = rgba!(0x12u8, 0x34, 0x56, 0xFF);
+        assert_eq!(opaque.flatten_on_white(), opaque.c);
+        assert_eq!(opaque.flatten_on_black(), opaque.c);
+        assert_eq!(rgba!(0x12u8, 0x34, 0x56, 0).flatten_on_white(), rgb!(0xFF, 0xFF, 0xFF));
+    }
+
+    #[test]
+    fn test_composite_on_checker() {
+        let checker = (rgb!(0xCC, 0xCC, 0xCC), rgb!(0x66, 0x66, 0x66));
+        let opaque = rgba!(0x12, 0x34, 0x56, 0xFF);
+        for &(x, y) in [(0, 0), (8, 0), (0, 8), (15, 3)].iter() {
+            assert_eq!(opaque.composite_on_checker(x, y, 8, checker), opaque.c);
+        }
+        let transparent = rgba!(0x12, 0x34, 0x56, 0);
+        assert_eq!(transparent.composite_on_checker(0, 0, 8, checker), checker.0);
+        assert_eq!(transparent.composite_on_checker(7, 7, 8, checker), checker.0);
+        assert_eq!(transparent.composite_on_checker(8, 0, 8, checker), checker.1);
+        assert_eq!(transparent.composite_on_checker(3, 12, 8, checker), checker.1);
+        assert_eq!(transparent.composite_on_checker(9, 9, 8, checker), checker.0);
+    }
+
+    #[test]
+    fn test_over() {
+        let background = rgba!(0x20u8, 0x40, 0x60, 0xFF);
+        let opaque = rgba!(0xC0u8, 0x80, 0x10, 0xFF);
+        assert_eq!(opaque.over(&background), opaque);
+        assert_eq!(rgba!(0xC0u8, 0x80, 0x10, 0).over(&background), background);
+
+        let half = Rgba::<f32, LinearRgb>::new(Rgb::new(0.8, 0.4, 0.2), 0.5);
+        let black = Rgba::<f32, LinearRgb>::new(Rgb::new(0., 0., 0.), 1.);
+        let over = half.over(&black);
+        assert!((over.c.r - 0.4).abs() < 1e-6 && (over.c.g - 0.2).abs() < 1e-6 && (over.c.b - 0.1).abs() < 1e-6, "{:?}", over);
+        assert_eq!(over.a, 1.);
+
+        // Both half transparent layers let a quarter of the light through
+        let over = half.over(&Rgba::new(Rgb::new(0., 0., 1.), 0.5));
+        assert!((over.a - 0.75).abs() < 1e-6 && (over.c.b - (0.1 + 0.25) / 0.75).abs() < 1e-6, "{:?}", over);
+        let clear = Rgba::<f32, LinearRgb>::new(Rgb::new(1., 1., 1.), 0.);
+        assert_eq!(clear.over(&clear).a, 0.);
+    }
+
+    #[test]
+    fn test_blend_modes() {
+        let gray = |c: f64, a: f64| Rgba::<f64, LinearRgb>::new(Rgb::splat(c), a);
+        let source = gray(0.5, 0.5);
+        let backdrop = gray(0.25, 1.);
+        let expected = [
+            (BlendMode::Multiply, 0.1875, 1.),
+            (BlendMode::Screen, 0.4375, 1.),
+            (BlendMode::Overlay, 0.25, 1.),
+            (BlendMode::Darken, 0.25, 1.),
+            (BlendMode::Lighten, 0.375, 1.),
+            (BlendMode::SrcOver, 0.375, 1.),
+            (BlendMode::DstOver, 0.25, 1.),
+            (BlendMode::SrcIn, 0.5, 0.5),
+            (BlendMode::DstOut, 0.25, 0.5),
+        ];
+        for &(mode, c, a) in expected.iter() {
+            let blended = source.blend(&backdrop, mode);
+            assert!((blended.c.r - c).abs() < 1e-12 && blended.c.r == blended.c.b && (blended.a - a).abs() < 1e-12,
+                "{:?}: {:?}", mode, blended);
+        }
+
+        // A translucent backdrop only mixes where it covers the source
+        let blended = source.blend(&gray(0.25, 0.5), BlendMode::Multiply);
+        assert!((blended.a - 0.75).abs() < 1e-12 && (blended.c.g - 0.21875 / 0.75).abs() < 1e-12, "{:?}", blended);
+        // Overlay screens light backdrops
+        let blended = source.blend(&gray(0.75, 1.), BlendMode::Overlay);
+        assert!((blended.c.r - (0.75 * 0.5 + 0.5 * 0.75)).abs() < 1e-12, "{:?}", blended);
+        assert_eq!(gray(0.5, 0.).blend(&gray(0.5, 0.), BlendMode::Screen).a, 0.);
+    }
+
+    #[test]
+    fn test_flatten_over() {
+        let background = rgb!(0x20u8, 0x40, 0x60);
+        assert_eq!(rgba!(0xC0u8, 0x80, 0x10, 0xFF).flatten_over(&background), rgb!(0xC0, 0x80, 0x10));
+        assert_eq!(rgba!(0xC0u8, 0x80, 0x10, 0).flatten_over(&background), background);
+        assert_eq!(rgba!(0u8, 0, 0, 0x80).flatten_over(&rgb!(0xFF, 0xFF, 0xFF)), rgba!(0u8, 0, 0, 0x80).flatten_on_white());
+        let half = Rgba::<f32, LinearRgb>::new(Rgb::new(0.8, 0.4, 0.2), 0.5);
+        let flat = half.flatten_over(&Rgb::new(0., 0., 0.));
+        assert!((flat.r - 0.4).abs() < 1e-6 && (flat.g - 0.2).abs() < 1e-6 && (flat.b - 0.1).abs() < 1e-6, "{:?}", flat);
+    }
+
+    #[test]
+    fn test_lerp_premultiplied() {
+        let red = rgba!(1f32, 0., 0., 1.);
+        let clear_blue = rgba!(0f32, 0., 1., 0.);
+        let mid = red.lerp(&clear_blue, 0.5);
+        // Still pure red, only more transparent
+        assert!((mid.c.r - 1.).abs() < 1e-6 && mid.c.g == 0. && mid.c.b == 0. && mid.a == 0.5, "{:?}", mid);
+        assert_eq!(red.lerp(&clear_blue, 0.), red);
+        assert_eq!(red.lerp(&clear_blue, 1.), clear_blue);
+
+        let half_blue = rgba!(0f32, 0., 1., 0.5);
+        let mid = red.lerp(&half_blue, 0.5);
+        assert!((mid.a - 0.75).abs() < 1e-6);
+        // Red weighs twice as much as blue in linear light
+        assert!((Srgb::to_linear(mid.c.r) - 2. / 3.).abs() < 1e-5, "{:?}", mid);
+        assert!((Srgb::to_linear(mid.c.b) - 1. / 3.).abs() < 1e-5, "{:?}", mid);
+    }
+
+    #[test]
+    fn test_composite_stack() {
+        let red = rgba!(1f32, 0., 0., 0.5);
+        let blue = rgba!(0f32, 0., 1., 0.5);
+        let red_on_top = composite_stack(&[blue, red], 1.);
+        let blue_on_top = composite_stack(&[red, blue], 1.);
+        assert!(red_on_top.c.r > red_on_top.c.b, "{:?}", red_on_top);
+        assert!(blue_on_top.c.b > blue_on_top.c.r, "{:?}", blue_on_top);
+        assert!((red_on_top.a - 0.75).abs() < 1e-6 && (blue_on_top.a - 0.75).abs() < 1e-6);
+        // 2/3 of the linear light comes from the top layer
+        assert!((Srgb::to_linear(red_on_top.c.r) - 2. / 3.).abs() < 1e-5, "{:?}", red_on_top);
+
+        let faded = composite_stack(&[blue, red], 0.5);
+        assert!((faded.a - 0.375).abs() < 1e-6, "{:?}", faded);
+        assert_eq!(faded.c, red_on_top.c);
+
+        let opaque = rgba!(0.2f32, 0.4, 0.6, 1.);
+        let top = composite_stack(&[red, opaque], 1.);
+        assert!((top.c.r - 0.2).abs() < 1e-6 && (top.c.g - 0.4).abs() < 1e-6 && (top.c.b - 0.6).abs() < 1e-6);
+        assert_eq!(composite_stack(&[], 1.), rgba!(0f32, 0., 0., 0.));
+    }
+
+    #[test]
+    fn test_splat() {
+        let c = Rgba::<f32>::splat(0.5);
+        assert_eq!((c.c.r, c.c.g, c.c.b, c.a), (0.5, 0.5, 0.5, 1.0));
+        assert_eq!(Rgba::<u8>::splat(0x80), rgba!(0x80, 0x80, 0x80, 0xFF));
+        assert_eq!(Rgba::<u8>::splat_with_alpha(0x80, 0x40), rgba!(0x80, 0x80, 0x80, 0x40));
+    }
+
+    #[test]
+    fn test_rgba8() {
+        assert_eq!(rgba!(1u8, 2, 3, 0x10).to_rgba8(), [1, 2, 3, 0x10]);
+        assert_eq!(Rgba::<u8>::from_rgba8([1, 2, 3, 0x10]), rgba!(1u8, 2, 3, 0x10));
+        assert_eq!(Rgba::<u8>::from_rgba8([1, 2, 3, 0x10]).c.to_rgbx(), [1, 2, 3, 0xFF]);
+    }
+
+    #[test]
+    fn test_rgba_eq() {
+        assert_eq!(rgba!(0x10u8, 0x20, 0x30, 0xFF), Rgba::<u8>::new(Rgb::new(0x10, 0x20, 0x30), 0xFF));
+        assert_ne!(rgba!(0x10u8, 0x20, 0x30, 0xFF), rgba!(0x10u8, 0x20, 0x30, 0x80));
+        assert_ne!(rgba!(0x10u8, 0x20, 0x30, 0xFF), rgba!(0x10u8, 0x21, 0x30, 0xFF));
+        assert_eq!(rgba!(0.5f32, 0.25, 1.0, 1.0), Rgba::<f32, Srgb>::new(Rgb::new(0.5, 0.25, 1.0), 1.0));
+        assert_ne!(rgba!(0.5f32, 0.25, 1.0, 1.0), rgba!(0.5f32, 0.25, 1.0, 0.5));
+        assert_eq!(Rgb::<f16>::new(f16::ONE, f16::ZERO, f16::ONE), Rgb::new(f16::ONE, f16::ZERO, f16::ONE));
+    }
+}
@@ -14,11 +14,48 @@
 // limitations under the License.
 
 //! Color channel conversions and utility methods
+//!
+//! Float to integer conversions round to the nearest value so converting an
+//! integer channel to a float and back always returns the original value.
 
 use num_traits::{Float, zero, one};
 use std::{u8, u16, u32};
 use half::f16;
 
+/// How float channels are quantized to integers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Ties round up, the default for all channel conversions.
+    #[default]
+    HalfUp,
+    /// Ties round to the even value, avoids biasing large batches upwards.
+    HalfEven,
+    /// Drops the fractional part.
+    Truncate,
+}
+
+impl RoundingMode {
+    /// Rounds a non negative value according to this mode.
+    #[inline]
+    pub fn round<T: Float>(self, x: T) -> T {
+        let half = one::<T>() / (one::<T>() + one());
+        match self {
+            RoundingMode::HalfUp => (x + half).floor(),
+            RoundingMode::HalfEven => {
+                let floor = x.floor();
+                if x - floor != half {
+                    x.round()
+                }else if floor % (one::<T>() + one()) == zero() {
+                    floor
+                }else{
+                    floor + one()
+                }
+            }
+            RoundingMode::Truncate => x.trunc(),
+        }
+    }
+}
+
 pub trait Channel: Copy + Sized + PartialOrd + PartialEq {
     type NearestFloat: Float + Channel;
 
@@ -29,6 +66,9 @@ pub trait Channel: Copy + Sized + PartialOrd + PartialEq {
     fn to_channel_u32(self) -> u32;
     fn to_channel_f16(self) -> f16;
     fn to_channel_f32(self) -> f32;
+    /// Normalized value as `f64`, integer channels map their range to
+    /// `[0, 1]`. Go back with `to_channel` or `Channel::from`, integer
+    /// channels round trip through `f64` unchanged.
     fn to_channel_f64(self) -> f64;
     fn to_nearest_precision_float(self) -> Self::NearestFloat;
 
@@ -134,9 +174,9 @@ impl Channel for u32 {
 impl Channel for f16 {
     type NearestFloat = f32;
     #[inline] fn from<T:Channel>(chan: T) -> f16 { chan.to_channel_f16() }
-    #[inline] fn to_channel_u8(self)  -> u8  { (self.to_f32() * u8::MAX as f32) as u8 }
-    #[inline] fn to_channel_u16(self) -> u16 { (self.to_f32() * u16::MAX as f32) as u16 }
-    #[inline] fn to_channel_u32(self) -> u32 { (self.to_f32() * u32::MAX as f32) as u32 }
+    #[inline] fn to_channel_u8(self)  -> u8  { (self.to_f32() * u8::MAX as f32).round() as u8 }
+    #[inline] fn to_channel_u16(self) -> u16 { (self.to_f32() * u16::MAX as f32).round() as u16 }
+    #[inline] fn to_channel_u32(self) -> u32 { (self.to_f32() * u32::MAX as f32).round() as u32 }
     #[inline] fn to_channel_f16(self) -> f16 { self }
     #[inline] fn to_channel_f32(self) -> f32 { self.to_f32() }
     #[inline] fn to_channel_f64(self) -> f64 { self.to_f64() }
@@ -168,9 +208,9 @@ impl Channel for f16 {
 impl Channel for f32 {
     type NearestFloat = f32;
     #[inline] fn from<T:Channel>(chan: T) -> f32 { chan.to_channel_f32() }
-    #[inline] fn to_channel_u8(self)  -> u8  { (self * (u8::MAX as f32)) as u8 }
-    #[inline] fn to_channel_u16(self) -> u16 { (self * (u16::MAX as f32)) as u16 }
-    #[inline] fn to_channel_u32(self) -> u32 { (self * (u32::MAX as f32)) as u32 }
+    #[inline] fn to_channel_u8(self)  -> u8  { (self * (u8::MAX as f32)).round() as u8 }
+    #[inline] fn to_channel_u16(self) -> u16 { (self * (u16::MAX as f32)).round() as u16 }
+    #[inline] fn to_channel_u32(self) -> u32 { (self * (u32::MAX as f32)).round() as u32 }
     #[inline] fn to_channel_f16(self) -> f16 { f16::from_f32(self) }
     #[inline] fn to_channel_f32(self) -> f32 { self }
     #[inline] fn to_channel_f64(self) -> f64 { self as f64 }
@@ -202,9 +242,9 @@ impl Channel for f32 {
 impl Channel for f64 {
     type NearestFloat = f64;
     #[inline] fn from<T:Channel>(chan: T) -> f64 { chan.to_channel_f64() }
-    #[inline] fn to_channel_u8(self)  -> u8  { (self * u8::MAX as f64) as u8 }
-    #[inline] fn to_channel_u16(self) -> u16 { (self * u16::MAX as f64) as u16 }
-    #[inline] fn to_channel_u32(self) -> u32 { (self * u32::MAX as f64) as u32 }
+    #[inline] fn to_channel_u8(self)  -> u8  { (self * u8::MAX as f64).round() as u8 }
+    #[inline] fn to_channel_u16(self) -> u16 { (self * u16::MAX as f64).round() as u16 }
+    #[inline] fn to_channel_u32(self) -> u32 { (self * u32::MAX as f64).round() as u32 }
     #[inline] fn to_channel_f16(self) -> f16 { f16::from_f64(self) }
     #[inline] fn to_channel_f32(self) -> f32 { self as f32 }
     #[inline] fn to_channel_f64(self) -> f64 { self }
@@ -306,14 +346,14 @@ mod tests {
     #[test]
     fn test_to_channel_f32() {
         assert_eq!(0.00f32.to_channel_u8(), 0x00);
-        assert_eq!(0.25f32.to_channel_u8(), 0x3F);
-        assert_eq!(0.50f32.to_channel_u8(), 0x7F);
+        assert_eq!(0.25f32.to_channel_u8(), 0x40);
+        assert_eq!(0.50f32.to_channel_u8(), 0x80);
         assert_eq!(0.75f32.to_channel_u8(), 0xBF);
         assert_eq!(1.00f32.to_channel_u8(), 0xFF);
 
         assert_eq!(0.00f32.to_channel_u16(), 0x0000);
-        assert_eq!(0.25f32.to_channel_u16(), 0x3FFF);
-        assert_eq!(0.50f32.to_channel_u16(), 0x7FFF);
+        assert_eq!(0.25f32.to_channel_u16(), 0x4000);
+        assert_eq!(0.50f32.to_channel_u16(), 0x8000);
         assert_eq!(0.75f32.to_channel_u16(), 0xBFFF);
         assert_eq!(1.00f32.to_channel_u16(), 0xFFFF);
 
@@ -334,14 +374,14 @@ mod tests {
     #[test]
     fn test_to_channel_f64() {
         assert_eq!(0.00f64.to_channel_u8(), 0x00);
-        assert_eq!(0.25f64.to_channel_u8(), 0x3F);
-        assert_eq!(0.50f64.to_channel_u8(), 0x7F);
+        assert_eq!(0.25f64.to_channel_u8(), 0x40);
+        assert_eq!(0.50f64.to_channel_u8(), 0x80);
         assert_eq!(0.75f64.to_channel_u8(), 0xBF);
         assert_eq!(1.00f64.to_channel_u8(), 0xFF);
 
         assert_eq!(0.00f64.to_channel_u16(), 0x0000);
-        assert_eq!(0.25f64.to_channel_u16(), 0x3FFF);
-        assert_eq!(0.50f64.to_channel_u16(), 0x7FFF);
+        assert_eq!(0.25f64.to_channel_u16(), 0x4000);
+        assert_eq!(0.50f64.to_channel_u16(), 0x8000);
         assert_eq!(0.75f64.to_channel_u16(), 0xBFFF);
         assert_eq!(1.00f64.to_channel_u16(), 0xFFFF);
 
@@ -358,4 +398,39 @@ mod tests {
         assert_eq!(0.50f64.invert_channel(), 0.50f64);
         assert_eq!(1.00f64.invert_channel(), 0.00f64);
     }
+
+    #[test]
+    fn test_u8_float_round_trip() {
+        for c in 0..=u8::MAX {
+            assert_eq!(c.to_channel_f32().to_channel_u8(), c);
+            assert_eq!(c.to_channel_f64().to_channel_u8(), c);
+            assert_eq!(c.to_channel_f16().to_channel_u8(), c);
+        }
+    }
+
+    #[test]
+    fn test_f64_round_trip() {
+        for v in 0..=u8::MAX {
+            assert_eq!(v.to_channel_f64().to_channel::<u8>(), v);
+        }
+        for v in 0..=u16::MAX {
+            assert_eq!(<u16 as Channel>::from(v.to_channel_f64()), v);
+        }
+        for &v in [0u32, 1, 0x1234_5678, u32::MAX - 1, u32::MAX].iter() {
+            assert_eq!(v.to_channel_f64().to_channel::<u32>(), v);
+        }
+        for &v in [0f32, 0.1, 0.5, 1. / 3., 1., 1.5, -0.25].iter() {
+            assert_eq!(v.to_channel_f64().to_channel::<f32>(), v);
+        }
+        assert_eq!(0xFFu8.to_channel_f64(), 1.);
+        assert_eq!(0x8000u16.to_channel_f64(), 0x8000 as f64 / 0xFFFF as f64);
+    }
+
+    #[test]
+    fn test_u16_float_round_trip() {
+        for c in 0..=u16::MAX {
+            assert_eq!(c.to_channel_f32().to_channel_u16(), c);
+            assert_eq!(c.to_channel_f64().to_channel_u16(), c);
+        }
+    }
 }
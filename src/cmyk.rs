@@ -0,0 +1,88 @@
+//! Naive device CMYK, without ICC profiles
+//!
+//! Converting 8 bit sRGB to a float `Cmyk` and back always returns the
+//! original color: the formulas are exact inverses and rounding only happens
+//! once, when going back to integers.
+
+use num_traits::{zero, one};
+use channel::{Channel, FloatChannel};
+use color_space::Srgb;
+use rgb::{Rgb, ToRgb};
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Cmyk<T> { pub c: T, pub m: T, pub y: T, pub k: T }
+
+impl<T> Cmyk<T> {
+    #[inline]
+    pub const fn new(c: T, m: T, y: T, k: T) -> Cmyk<T> {
+        Cmyk { c, m, y, k }
+    }
+}
+
+pub trait ToCmyk {
+    fn to_cmyk<U: FloatChannel>(&self) -> Cmyk<U>;
+}
+
+impl<T: Channel> ToCmyk for Rgb<T, Srgb> {
+    fn to_cmyk<U: FloatChannel>(&self) -> Cmyk<U> {
+        let r: U = self.r.to_channel();
+        let g: U = self.g.to_channel();
+        let b: U = self.b.to_channel();
+        let k = one::<U>() - r.max(g).max(b);
+        // Pure black has no ink other than K
+        if k >= one() {
+            return Cmyk::new(zero(), zero(), zero(), one());
+        }
+        let white = one::<U>() - k;
+        Cmyk::new(
+            (white - r) / white,
+            (white - g) / white,
+            (white - b) / white,
+            k,
+        )
+    }
+}
+
+impl<T: FloatChannel> ToRgb for Cmyk<T> {
+    type Standard = Srgb;
+    fn to_rgb<U: Channel>(&self) -> Rgb<U, Srgb> {
+        let white = one::<T>() - self.k;
+        Rgb::new(
+            ((one::<T>() - self.c) * white).to_channel(),
+            ((one::<T>() - self.m) * white).to_channel(),
+            ((one::<T>() - self.y) * white).to_channel(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cmyk, ToCmyk};
+    use rgb::{Rgb, ToRgb};
+
+    #[test]
+    fn test_u8_round_trip() {
+        for r in (0..=255u8).step_by(5) {
+            for g in (0..=255u8).step_by(3) {
+                for b in (0..=255u8).step_by(7) {
+                    let c = rgb!(r, g, b);
+                    assert_eq!(c.to_cmyk::<f32>().to_rgb::<u8>(), c);
+                    assert_eq!(c.to_cmyk::<f64>().to_rgb::<u8>(), c);
+                }
+            }
+        }
+        for v in 0..=255u8 {
+            let c = rgb!(v, 0xFF - v, v / 2);
+            assert_eq!(c.to_cmyk::<f32>().to_rgb::<u8>(), c);
+        }
+    }
+
+    #[test]
+    fn test_to_cmyk() {
+        assert_eq!(rgb!(0xFFu8, 0xFF, 0xFF).to_cmyk::<f32>(), Cmyk::new(0., 0., 0., 0.));
+        assert_eq!(rgb!(0u8, 0, 0).to_cmyk::<f32>(), Cmyk::new(0., 0., 0., 1.));
+        assert_eq!(rgb!(0u8, 0xFF, 0xFF).to_cmyk::<f32>(), Cmyk::new(1., 0., 0., 0.));
+        assert_eq!(Cmyk::new(1f32, 0., 0., 0.).to_rgb::<u8>(), Rgb::new(0, 0xFF, 0xFF));
+        assert_eq!(Cmyk::new(0f32, 0., 0., 1.).to_rgb::<u8>(), Rgb::new(0, 0, 0));
+    }
+}
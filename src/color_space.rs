@@ -2,8 +2,13 @@ use xyz::Xyz;
 use yxy::Yxy;
 use channel::Channel;
 use rgb::Rgb;
-use num_traits::{Float, cast};
+use num_traits::{Float, cast, one, zero};
 use std::ops::{Mul, Index};
+use error::ColorError;
+
+pub use adobe_rgb::AdobeRgb;
+pub use p3::{DisplayP3, DciP3};
+pub use rec2020::Rec2020;
 
 
 pub trait MatrixColorSpace{
@@ -22,21 +27,45 @@ pub trait MatrixColorSpace{
     }
 
     fn to_xyz_matrix<T: Channel + Float>() -> Mat3<T>;
-    fn to_rgb_matrix<T: Channel + Float>() -> Mat3<T>;
+    /// RGB to XYZ matrix computed from the chromaticities of the primaries
+    /// and the white point, scaled so RGB white maps to the white point.
+    fn matrix_from_primaries<T: Channel + Float>() -> Mat3<T> {
+        primaries_matrix(&Self::red(), &Self::green(), &Self::blue(), &Self::WhitePoint::xyz())
+            .expect("The primaries of a color space must be independent")
+    }
+    /// Inverse of `to_xyz_matrix`. The default inverts it on every call and
+    /// panics if it's singular, it's only a fallback: spaces should provide
+    /// their published matrix, which is both faster and more precise.
+    fn to_rgb_matrix<T: Channel + Float>() -> Mat3<T> {
+        Self::to_xyz_matrix().inverse().expect("The primaries of a color space must be independent")
+    }
 }
 
 pub trait TransferFunction: 'static{
     fn from_linear<T: Float>(x: T) -> T;
+    /// Decodes `x` into linear light. Inputs outside `[0, 1]` are not clamped,
+    /// they follow the curve so extended range values decode to values outside
+    /// `[0, 1]` too.
     fn to_linear<T: Float>(x: T) -> T;
 }
 
 pub trait WhitePoint: Default{
+    /// XYZ of the white point, normalized so its Y is 1 like every `Xyz`
+    /// in this crate.
     fn xyz<T: Channel + Float>() -> Xyz<T,D50>;
+    /// Name used to tag serialized colors with their white point. It's
+    /// written to files so it must stay stable. Defaults to `"custom"`, which
+    /// doesn't tell custom white points apart, override it to do so.
+    fn name() -> &'static str {
+        "custom"
+    }
 }
 
 #[derive(Default, Clone, Copy, Debug)]
 pub struct A;
 #[derive(Default, Clone, Copy, Debug)]
+pub struct C;
+#[derive(Default, Clone, Copy, Debug)]
 pub struct D50;
 #[derive(Default, Clone, Copy, Debug)]
 pub struct D55;
@@ -46,18 +75,39 @@ pub struct D65;
 pub struct D75;
 #[derive(Default, Clone, Copy, Debug)]
 pub struct E;
+#[derive(Default, Clone, Copy, Debug)]
+pub struct Dci;
 
 // Incandescent / Tungsten
 impl WhitePoint for A {
     fn xyz<T: Channel + Float>() -> Xyz<T,D50> {
         Xyz::new(1.09850.to_channel(), 1.0.to_channel(), 0.35585.to_channel())
     }
+
+    fn name() -> &'static str {
+        "A"
+    }
+}
+
+// Average daylight, superseded by D65
+impl WhitePoint for C {
+    fn xyz<T: Channel + Float>() -> Xyz<T,D50> {
+        Xyz::new(0.98074.to_channel(), 1.0.to_channel(), 1.18232.to_channel())
+    }
+
+    fn name() -> &'static str {
+        "C"
+    }
 }
 
 // ICC profile PCS
 impl WhitePoint for D50 {
     fn xyz<T: Channel + Float>() -> Xyz<T,D50> {
-        Xyz::new(0.96422.to_channel(), 1.0.to_channel(), 0.8251.to_channel())
+        Xyz::new(0.96422.to_channel(), 1.0.to_channel(), 0.82521.to_channel())
+    }
+
+    fn name() -> &'static str {
+        "D50"
     }
 }
 
@@ -66,6 +116,10 @@ impl WhitePoint for D55 {
     fn xyz<T: Channel + Float>() -> Xyz<T,D50> {
         Xyz::new(0.95682.to_channel(), 1.0.to_channel(), 0.92149.to_channel())
     }
+
+    fn name() -> &'static str {
+        "D55"
+    }
 }
 
 // Daylight, sRGB, Adobe-RGB
@@ -73,6 +127,10 @@ impl WhitePoint for D65 {
     fn xyz<T: Channel + Float>() -> Xyz<T,D50> {
         Xyz::new(0.95047.to_channel(), 1.0.to_channel(), 1.08883.to_channel())
     }
+
+    fn name() -> &'static str {
+        "D65"
+    }
 }
 
 // North sky daylight
@@ -80,6 +138,10 @@ impl WhitePoint for D75 {
     fn xyz<T: Channel + Float>() -> Xyz<T,D50> {
         Xyz::new(0.94972.to_channel(), 1.0.to_channel(), 1.22638.to_channel())
     }
+
+    fn name() -> &'static str {
+        "D75"
+    }
 }
 
 // Equal energy
@@ -87,8 +149,22 @@ impl WhitePoint for E {
     fn xyz<T: Channel + Float>() -> Xyz<T,D50> {
         Xyz::new(1.0.to_channel(), 1.0.to_channel(), 1.0.to_channel())
     }
+
+    fn name() -> &'static str {
+        "E"
+    }
 }
 
+// DCI cinema projector white, x = 0.314, y = 0.351
+impl WhitePoint for Dci {
+    fn xyz<T: Channel + Float>() -> Xyz<T,D50> {
+        Xyz::new((0.314 / 0.351).to_channel(), 1.0.to_channel(), (0.335 / 0.351).to_channel())
+    }
+
+    fn name() -> &'static str {
+        "DCI"
+    }
+}
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Srgb;
@@ -126,7 +202,7 @@ impl TransferFunction for Srgb{
         if x > cast(0.0031308).unwrap() {
             cast::<f32, T>(1.055).unwrap() * x.powf(cast(1. / 2.4).unwrap()) - cast(0.055).unwrap()
         }else{
-            cast::<f32, T>(12.95).unwrap() * x
+            cast::<f32, T>(12.92).unwrap() * x
         }
     }
 
@@ -152,7 +228,35 @@ impl TransferFunction for LinearRgb {
     }
 }
 
+/// Linear RGB uses the sRGB primaries so it converts to XYZ with the sRGB
+/// matrices, skipping the transfer function.
+impl MatrixColorSpace for LinearRgb {
+    type WhitePoint = D65;
+
+    fn red<T: Channel + Float>() -> Yxy<T, D50> {
+        Srgb::red()
+    }
+    fn green<T: Channel + Float>() -> Yxy<T, D50> {
+        Srgb::green()
+    }
+    fn blue<T: Channel + Float>() -> Yxy<T, D50> {
+        Srgb::blue()
+    }
+    fn to_xyz_matrix<T: Channel + Float>() -> Mat3<T>{
+        Srgb::to_xyz_matrix()
+    }
+    fn to_rgb_matrix<T: Channel + Float>() -> Mat3<T>{
+        Srgb::to_rgb_matrix()
+    }
+}
+
+/// Row major 3x3 matrix used for color space conversions.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Mat3<T>(pub [T;9]);
+
+/// Column vector holding the three components of a color, in order, for
+/// custom matrix math. Converts from and into `Rgb` and `Xyz`.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Vec3<T>(pub [T;3]);
 
 impl<T: Channel + Float + Mul> Mul<Vec3<T>> for Mat3<T>{
@@ -178,6 +282,204 @@ impl<T: Channel + Float + Mul> Mul<Vec3<T>> for Mat3<T>{
     }
 }
 
+impl<T: Float> Mat3<T> {
+    pub fn determinant(&self) -> T {
+        let [m0, m1, m2, m3, m4, m5, m6, m7, m8] = self.0;
+        m0 * (m4 * m8 - m5 * m7) - m1 * (m3 * m8 - m5 * m6) + m2 * (m3 * m7 - m4 * m6)
+    }
+
+    /// Inverse of the matrix or `None` if it's singular, that is its
+    /// determinant is within epsilon of 0.
+    pub fn inverse(&self) -> Option<Mat3<T>> {
+        let det = self.determinant();
+        if det.abs() <= T::epsilon() {
+            return None;
+        }
+        let [m0, m1, m2, m3, m4, m5, m6, m7, m8] = self.0;
+        let inv = one::<T>() / det;
+        Some(Mat3([
+            (m4 * m8 - m5 * m7) * inv, (m2 * m7 - m1 * m8) * inv, (m1 * m5 - m2 * m4) * inv,
+            (m5 * m6 - m3 * m8) * inv, (m0 * m8 - m2 * m6) * inv, (m2 * m3 - m0 * m5) * inv,
+            (m3 * m7 - m4 * m6) * inv, (m1 * m6 - m0 * m7) * inv, (m0 * m4 - m1 * m3) * inv,
+        ]))
+    }
+}
+
+/// Bradford chromatic adaptation from D50 to D65 XYZ, the matrix CSS and
+/// browsers use to convert between Lab and sRGB.
+/// See http://www.brucelindbloom.com/index.html?Eqn_ChromAdapt.html
+pub const BRADFORD_D50_TO_D65: Mat3<f32> = Mat3([
+     0.9555766, -0.0230393,  0.0631636,
+    -0.0282895,  1.0099416,  0.0210077,
+     0.0122982, -0.0204830,  1.3299098,
+]);
+
+/// Bradford chromatic adaptation from D65 to D50 XYZ, the inverse of
+/// `BRADFORD_D50_TO_D65`.
+pub const BRADFORD_D65_TO_D50: Mat3<f32> = Mat3([
+     1.0478112,  0.0228866, -0.0501270,
+     0.0295424,  0.9904844, -0.0170491,
+    -0.0092345,  0.0150436,  0.7521316,
+]);
+
+/// Chromatic adaptation transform, selects the cone response space the
+/// white points are scaled in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Adaptation {
+    /// Von Kries with the Hunt-Pointer-Estevez cone responses.
+    VonKries,
+    /// Bradford, the transform ICC profiles and CSS use.
+    #[default]
+    Bradford,
+    /// The CIECAM02 transform.
+    Cat02,
+}
+
+// Von Kries (Hunt-Pointer-Estevez) cone response matrix
+const VON_KRIES: [f64; 9] = [
+     0.40024,  0.70760, -0.08081,
+    -0.22630,  1.16532,  0.04570,
+     0.0,      0.0,      0.91822,
+];
+
+const VON_KRIES_INV: [f64; 9] = [
+     1.8599363874558, -1.1293816185801,  0.2198974095962,
+     0.3611914362418,  0.6388124632850, -0.0000063705968,
+     0.0,              0.0,              1.0890636230969,
+];
+
+// Bradford cone response matrix
+const BRADFORD: [f64; 9] = [
+     0.8951,  0.2664, -0.1614,
+    -0.7502,  1.7135,  0.0367,
+     0.0389, -0.0685,  1.0296,
+];
+
+const BRADFORD_INV: [f64; 9] = [
+     0.9869929054667, -0.1470542564210,  0.1599626516637,
+     0.4323052697234,  0.5183602715368,  0.0492912282129,
+    -0.0085286645752,  0.0400428216541,  0.9684866957875,
+];
+
+// CIECAM02 cone response matrix
+const CAT02: [f64; 9] = [
+     0.7328,  0.4296, -0.1624,
+    -0.7036,  1.6975,  0.0061,
+     0.0030,  0.0136,  0.9834,
+];
+
+const CAT02_INV: [f64; 9] = [
+     1.0961238208355, -0.2788690002183,  0.1827451793828,
+     0.4543690419754,  0.4735331543074,  0.0720978037172,
+    -0.0096276087384, -0.0056980312161,  1.0153256399545,
+];
+
+impl Adaptation {
+    fn cone_response(self) -> (&'static [f64; 9], &'static [f64; 9]) {
+        match self {
+            Adaptation::VonKries => (&VON_KRIES, &VON_KRIES_INV),
+            Adaptation::Bradford => (&BRADFORD, &BRADFORD_INV),
+            Adaptation::Cat02 => (&CAT02, &CAT02_INV),
+        }
+    }
+}
+
+fn mat3_mul<T: Float>(a: Mat3<T>, b: Mat3<T>) -> Mat3<T> {
+    let (a, b) = (a.0, b.0);
+    let at = |row: usize, col: usize| {
+        a[row * 3] * b[col] + a[row * 3 + 1] * b[3 + col] + a[row * 3 + 2] * b[6 + col]
+    };
+    Mat3([
+        at(0, 0), at(0, 1), at(0, 2),
+        at(1, 0), at(1, 1), at(1, 2),
+        at(2, 0), at(2, 1), at(2, 2),
+    ])
+}
+
+/// Chromatic adaptation matrix from the `From` to the `To` white point with
+/// `method`, to be applied to XYZ values.
+pub fn adaptation_matrix<From: WhitePoint, To: WhitePoint, T: Channel + Float>(method: Adaptation) -> Mat3<T> {
+    let (cone, cone_inv) = method.cone_response();
+    let cone = Mat3(cone.map(|v| cast(v).unwrap()));
+    let cone_inv = Mat3(cone_inv.map(|v| cast(v).unwrap()));
+    let src = From::xyz::<T>();
+    let dst = To::xyz::<T>();
+    let src = cone * Vec3([src.x, src.y, src.z]);
+    let dst = cone * Vec3([dst.x, dst.y, dst.z]);
+    let z = zero();
+    let scale = Mat3([
+        dst.0[0] / src.0[0], z, z,
+        z, dst.0[1] / src.0[1], z,
+        z, z, dst.0[2] / src.0[2],
+    ]);
+    mat3_mul(cone_inv, mat3_mul(scale, cone))
+}
+
+/// Bradford chromatic adaptation matrix from the `From` to the `To` white
+/// point, to be applied to XYZ values.
+pub fn bradford_matrix<From: WhitePoint, To: WhitePoint, T: Channel + Float>() -> Mat3<T> {
+    adaptation_matrix::<From, To, T>(Adaptation::Bradford)
+}
+
+// Columns hold the XYZ of each primary with a luminance of 1
+fn chromaticity_matrix<T: Float>(red: &Yxy<T, D50>, green: &Yxy<T, D50>, blue: &Yxy<T, D50>) -> Mat3<T> {
+    let column = |p: &Yxy<T, D50>| [p.x / p.y, one(), (one::<T>() - p.x - p.y) / p.y];
+    let [xr, yr, zr] = column(red);
+    let [xg, yg, zg] = column(green);
+    let [xb, yb, zb] = column(blue);
+    Mat3([
+        xr, xg, xb,
+        yr, yg, yb,
+        zr, zg, zb,
+    ])
+}
+
+// Scales the chromaticity matrix columns so RGB white adds up to `white`
+fn primaries_matrix<T: Channel + Float>(red: &Yxy<T, D50>, green: &Yxy<T, D50>, blue: &Yxy<T, D50>, white: &Xyz<T, D50>) -> Option<Mat3<T>> {
+    let primaries = chromaticity_matrix(red, green, blue);
+    let [sr, sg, sb] = (primaries.inverse()? * Vec3([white.x, white.y, white.z])).0;
+    let [xr, xg, xb, yr, yg, yb, zr, zg, zb] = primaries.0;
+    Some(Mat3([
+        xr * sr, xg * sg, xb * sb,
+        yr * sr, yg * sg, yb * sb,
+        zr * sr, zg * sg, zb * sb,
+    ]))
+}
+
+/// RGB space defined at runtime by its primaries and white point, for
+/// conversions between linear RGB and XYZ.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DynamicRgbSpace {
+    to_xyz: Mat3<f64>,
+    to_rgb: Mat3<f64>,
+}
+
+impl DynamicRgbSpace {
+    /// Builds the space from the chromaticities of its primaries and its white
+    /// point. Fails with `ColorError::DegenerateSpace` if the primaries are
+    /// collinear or have no luminance, since they don't span a color space.
+    pub fn new(red: Yxy<f64, D50>, green: Yxy<f64, D50>, blue: Yxy<f64, D50>, white: Xyz<f64, D50>) -> Result<DynamicRgbSpace, ColorError> {
+        const MIN_DETERMINANT: f64 = 1e-9;
+        if red.y <= 0. || green.y <= 0. || blue.y <= 0. {
+            return Err(ColorError::DegenerateSpace);
+        }
+        if chromaticity_matrix(&red, &green, &blue).determinant().abs() < MIN_DETERMINANT {
+            return Err(ColorError::DegenerateSpace);
+        }
+        let to_xyz = primaries_matrix(&red, &green, &blue, &white).ok_or(ColorError::DegenerateSpace)?;
+        let to_rgb = to_xyz.inverse().ok_or(ColorError::DegenerateSpace)?;
+        Ok(DynamicRgbSpace { to_xyz, to_rgb })
+    }
+
+    pub fn to_xyz_matrix(&self) -> Mat3<f64> {
+        self.to_xyz
+    }
+
+    pub fn to_rgb_matrix(&self) -> Mat3<f64> {
+        self.to_rgb
+    }
+}
+
 impl<T: Channel + Float, W> From<Xyz<T,W>> for Vec3<T> {
     fn from(xyz: Xyz<T,W>) -> Self {
         Vec3([xyz.x, xyz.y, xyz.z])
@@ -190,9 +492,168 @@ impl<T: Channel, S> From<Rgb<T, S>> for Vec3<T> {
     }
 }
 
+impl<T: Channel + Float, W: WhitePoint> From<Vec3<T>> for Xyz<T,W> {
+    fn from(v: Vec3<T>) -> Self {
+        let [x, y, z] = v.0;
+        Xyz::new(x, y, z)
+    }
+}
+
+impl<T, S> From<Vec3<T>> for Rgb<T, S> {
+    fn from(v: Vec3<T>) -> Self {
+        let [r, g, b] = v.0;
+        Rgb::new(r, g, b)
+    }
+}
+
 impl<T> Index<usize> for Vec3<T>{
     type Output = T;
     fn index(&self, index: usize) -> &T{
         self.0.index(index)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AdobeRgb, DisplayP3, DciP3, Rec2020};
+    use super::{Vec3, Mat3, A, C, D50, D55, D65, D75, E, DynamicRgbSpace, bradford_matrix, MatrixColorSpace, Srgb, WhitePoint};
+    use super::{BRADFORD_D50_TO_D65, BRADFORD_D65_TO_D50};
+    use error::ColorError;
+    use rgb::Rgb;
+    use xyz::Xyz;
+    use yxy::Yxy;
+    use channel::Channel;
+    use num_traits::Float;
+
+    #[test]
+    fn test_dynamic_space() {
+        let srgb = DynamicRgbSpace::new(Srgb::red(), Srgb::green(), Srgb::blue(), D65::xyz()).unwrap();
+        let expected = Srgb::to_xyz_matrix::<f64>();
+        for (m, e) in srgb.to_xyz_matrix().0.iter().zip(expected.0.iter()) {
+            assert!((m - e).abs() < 1e-3, "{:?}", srgb.to_xyz_matrix());
+        }
+        let identity = srgb.to_rgb_matrix() * (srgb.to_xyz_matrix() * Vec3([0.2, 0.5, 0.9]));
+        assert!((identity[0] - 0.2).abs() < 1e-12 && (identity[1] - 0.5).abs() < 1e-12 && (identity[2] - 0.9).abs() < 1e-12);
+
+        let collinear = DynamicRgbSpace::new(
+            Yxy::new(0.1, 0.1, 1.),
+            Yxy::new(0.3, 0.3, 1.),
+            Yxy::new(0.5, 0.5, 1.),
+            D65::xyz(),
+        );
+        assert_eq!(collinear, Err(ColorError::DegenerateSpace));
+        let no_luminance = DynamicRgbSpace::new(Yxy::new(0.7, 0., 1.), Srgb::green(), Srgb::blue(), D65::xyz());
+        assert_eq!(no_luminance, Err(ColorError::DegenerateSpace));
+    }
+
+    #[test]
+    fn test_bradford() {
+        for v in [[1f32, 0., 0.], [0., 1., 0.], [0., 0., 1.], [0.3, 0.5, 0.2]].iter() {
+            let back = BRADFORD_D65_TO_D50 * (BRADFORD_D50_TO_D65 * Vec3(*v));
+            for i in 0..3 {
+                assert!((back[i] - v[i]).abs() < 1e-6, "{:?} {:?}", v, back);
+            }
+        }
+        let d65 = BRADFORD_D50_TO_D65 * Vec3([0.96422, 1., 0.82521]);
+        assert!((d65[0] - 0.95047).abs() < 1e-5 && (d65[1] - 1.).abs() < 1e-5 && (d65[2] - 1.08883).abs() < 1e-5, "{:?}", d65);
+        let d50 = BRADFORD_D65_TO_D50 * Vec3([0.95047, 1., 1.08883]);
+        assert!((d50[0] - 0.96422).abs() < 1e-5 && (d50[1] - 1.).abs() < 1e-5 && (d50[2] - 0.82521).abs() < 1e-5, "{:?}", d50);
+    }
+
+    #[test]
+    fn test_default_white_point_name() {
+        #[derive(Default)]
+        struct Studio;
+        impl WhitePoint for Studio {
+            fn xyz<T: Channel + Float>() -> Xyz<T, D50> {
+                D50::xyz()
+            }
+        }
+        assert_eq!(Studio::name(), "custom");
+        assert_eq!(D65::name(), "D65");
+    }
+
+    #[test]
+    fn test_illuminant_chromaticities() {
+        fn xy<Wp: WhitePoint>() -> (f64, f64) {
+            let xyz = Wp::xyz::<f64>();
+            let sum = xyz.x + xyz.y + xyz.z;
+            (xyz.x / sum, xyz.y / sum)
+        }
+        let expected = [
+            (xy::<A>(), (0.44757, 0.40745)),
+            (xy::<C>(), (0.31006, 0.31616)),
+            (xy::<D50>(), (0.34567, 0.35850)),
+            (xy::<D55>(), (0.33242, 0.34743)),
+            (xy::<D65>(), (0.31271, 0.32902)),
+            (xy::<D75>(), (0.29902, 0.31485)),
+            (xy::<E>(), (1. / 3., 1. / 3.)),
+        ];
+        for &((x, y), (ex, ey)) in expected.iter() {
+            assert!((x - ex).abs() < 5e-5 && (y - ey).abs() < 5e-5, "({}, {}) vs ({}, {})", x, y, ex, ey);
+        }
+    }
+
+    #[test]
+    fn test_bradford_matrix() {
+        let m = bradford_matrix::<D65, D50, f32>();
+        for (a, b) in m.0.iter().zip(BRADFORD_D65_TO_D50.0.iter()) {
+            assert!((a - b).abs() < 5e-4, "{:?}", m);
+        }
+    }
+
+    #[test]
+    fn test_matrix_from_primaries() {
+        let derived = Srgb::matrix_from_primaries::<f64>();
+        for (d, e) in derived.0.iter().zip(Srgb::to_xyz_matrix::<f64>().0.iter()) {
+            assert!((d - e).abs() < 1e-4, "{:?}", derived);
+        }
+    }
+
+    #[test]
+    fn test_derived_rgb_matrix() {
+        fn check<S: MatrixColorSpace>() {
+            let derived = S::to_xyz_matrix::<f64>().inverse().unwrap();
+            for (d, e) in derived.0.iter().zip(S::to_rgb_matrix::<f64>().0.iter()) {
+                assert!((d - e).abs() < 1e-6, "{:?}", derived);
+            }
+        }
+        check::<Srgb>();
+        check::<AdobeRgb>();
+        check::<DisplayP3>();
+        check::<DciP3>();
+        check::<Rec2020>();
+
+        // Spaces without a published inverse fall back to inverting
+        struct Forward;
+        impl MatrixColorSpace for Forward {
+            type WhitePoint = D65;
+            fn red<T: Channel + Float>() -> Yxy<T, D50> { Srgb::red() }
+            fn green<T: Channel + Float>() -> Yxy<T, D50> { Srgb::green() }
+            fn blue<T: Channel + Float>() -> Yxy<T, D50> { Srgb::blue() }
+            fn to_xyz_matrix<T: Channel + Float>() -> Mat3<T> { Srgb::to_xyz_matrix() }
+        }
+        check::<Forward>();
+    }
+
+    #[test]
+    fn test_mat3_singular() {
+        assert_eq!(Mat3([1., 2., 3., 2., 4., 6., 0., 1., 0.]).determinant(), 0.);
+        assert!(Mat3([1., 2., 3., 2., 4., 6., 0., 1., 0.]).inverse().is_none());
+        assert_eq!(Mat3([2., 0., 0., 0., 4., 0., 0., 0., 0.5]).inverse(), Some(Mat3([0.5, 0., 0., 0., 0.25, 0., 0., 0., 2.])));
+    }
+
+    #[test]
+    fn test_vec3_round_trip() {
+        let rgb = rgb!(0.25f32, 0.5, 0.75);
+        let v: Vec3<f32> = rgb.into();
+        assert_eq!(v, Vec3([0.25, 0.5, 0.75]));
+        let back: Rgb<f32> = v.into();
+        assert_eq!(back, rgb);
+
+        let xyz = Xyz::<f64, D65>::new(0.1, 0.2, 0.3);
+        let v = Mat3([2., 0., 0., 0., 1., 0., 0., 0., 0.5]) * Vec3::from(xyz);
+        let scaled: Xyz<f64, D65> = v.into();
+        assert_eq!((scaled.x, scaled.y, scaled.z), (0.2, 0.2, 0.15));
+    }
+}
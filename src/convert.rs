@@ -0,0 +1,188 @@
+//! Conversions between color spaces chosen at runtime
+
+use angle::Deg;
+use error::ParseError;
+use color_space::{D65, LinearRgb, MatrixColorSpace, Srgb, Vec3};
+use hsl::{Hsl, ToHsl};
+use hsv::{Hsv, ToHsv};
+use lab::{Lab, ToLab};
+use oklab::{Oklab, ToOklab};
+use oklch::{Oklch, ToOklch};
+use rgb::{Rgb, ToRgb};
+use xyz::{Xyz, ToXyz};
+use std::fmt;
+use std::str::FromStr;
+
+/// Color spaces `convert_between` can convert from and to. Components are
+/// in the order and units of the matching type, e.g. hues of `Hsl` and
+/// `Hsv` in degrees and of `Oklch` in radians. XYZ and Lab are relative to
+/// D65.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    Srgb,
+    LinearRgb,
+    Xyz,
+    Lab,
+    Hsl,
+    Hsv,
+    Oklab,
+    Oklch,
+}
+
+impl ColorSpace {
+    /// Every color space, in declaration order.
+    pub const ALL: [ColorSpace; 8] = [
+        ColorSpace::Srgb, ColorSpace::LinearRgb, ColorSpace::Xyz, ColorSpace::Lab,
+        ColorSpace::Hsl, ColorSpace::Hsv, ColorSpace::Oklab, ColorSpace::Oklch,
+    ];
+
+    /// Canonical lowercase name, as used by `Display` and `FromStr`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "srgb",
+            ColorSpace::LinearRgb => "linear-rgb",
+            ColorSpace::Xyz => "xyz",
+            ColorSpace::Lab => "lab",
+            ColorSpace::Hsl => "hsl",
+            ColorSpace::Hsv => "hsv",
+            ColorSpace::Oklab => "oklab",
+            ColorSpace::Oklch => "oklch",
+        }
+    }
+}
+
+impl fmt::Display for ColorSpace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses the canonical name of a color space, ignoring case.
+impl FromStr for ColorSpace {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<ColorSpace, ParseError> {
+        ColorSpace::ALL.iter()
+            .find(|space| space.name().eq_ignore_ascii_case(s))
+            .copied()
+            .ok_or(ParseError::UnknownColorSpace)
+    }
+}
+
+fn to_xyz(v: [f64; 3], from: ColorSpace) -> Xyz<f64, D65> {
+    match from {
+        ColorSpace::Srgb => Rgb::<f64, Srgb>::new(v[0], v[1], v[2]).to_xyz(),
+        ColorSpace::LinearRgb => Rgb::<f64, LinearRgb>::new(v[0], v[1], v[2]).to_xyz(),
+        ColorSpace::Xyz => Xyz::new(v[0], v[1], v[2]),
+        ColorSpace::Lab => Lab::<f64, D65>::new(v[0], v[1], v[2]).to_xyz(),
+        ColorSpace::Hsl => Hsl::<f64, Srgb>::new(Deg(v[0]), v[1], v[2]).to_rgb::<f64>().to_xyz(),
+        ColorSpace::Hsv => Hsv::<f64, Srgb>::new(Deg(v[0]), v[1], v[2]).to_rgb::<f64>().to_xyz(),
+        ColorSpace::Oklab => Oklab::new(v[0], v[1], v[2]).to_xyz(),
+        ColorSpace::Oklch => Oklch::new(v[0], v[1], v[2]).to_oklab::<f64>().to_xyz(),
+    }
+}
+
+fn from_xyz(xyz: Xyz<f64, D65>, to: ColorSpace) -> [f64; 3] {
+    match to {
+        ColorSpace::Srgb => {
+            let c: Rgb<f64, Srgb> = xyz.to_rgb();
+            [c.r, c.g, c.b]
+        }
+        ColorSpace::LinearRgb => (Srgb::to_rgb_matrix() * Vec3::from(xyz)).0,
+        ColorSpace::Xyz => [xyz.x, xyz.y, xyz.z],
+        ColorSpace::Lab => {
+            let c: Lab<f64, D65> = xyz.to_lab();
+            [c.l, c.a, c.b]
+        }
+        ColorSpace::Hsl => {
+            let c: Hsl<f64, Srgb> = xyz.to_rgb::<f64>().to_hsl();
+            [c.h.0, c.s, c.l]
+        }
+        ColorSpace::Hsv => {
+            let c: Hsv<f64, Srgb> = xyz.to_rgb::<f64>().to_hsv();
+            [c.h.0, c.s, c.v]
+        }
+        ColorSpace::Oklab => {
+            let c: Oklab<f64> = xyz.to_oklab();
+            [c.l, c.a, c.b]
+        }
+        ColorSpace::Oklch => {
+            let c: Oklch<f64> = xyz.to_oklab::<f64>().to_oklch();
+            [c.l, c.c, c.h]
+        }
+    }
+}
+
+/// Converts `value` from the `from` color space to `to`, going through XYZ.
+/// Computed in `f64` so the result is as precise as `f32` allows.
+pub fn convert_between(value: &[f32; 3], from: ColorSpace, to: ColorSpace) -> [f32; 3] {
+    if from == to {
+        return *value;
+    }
+    let v = [value[0] as f64, value[1] as f64, value[2] as f64];
+    let [a, b, c] = from_xyz(to_xyz(v, from), to);
+    [a as f32, b as f32, c as f32]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColorSpace, convert_between};
+    use error::ParseError;
+    use color_space::{D65, Srgb};
+    use hsl::{Hsl, ToHsl};
+    use lab::{Lab, ToLab};
+    use oklab::{Oklab, ToOklab};
+    use rgb::{Rgb, ToRgb};
+    use xyz::ToXyz;
+    use angle::Deg;
+
+    fn assert_close(a: [f32; 3], b: [f32; 3], eps: f32) {
+        assert!(a.iter().zip(b.iter()).all(|(a, b)| (a - b).abs() < eps), "{:?} {:?}", a, b);
+    }
+
+    #[test]
+    fn test_convert_between() {
+        let srgb = Rgb::<f32, Srgb>::new(0.2, 0.6, 0.9);
+        let lab: Lab<f32, D65> = srgb.to_xyz::<f32>().to_lab();
+        assert_close(convert_between(&[0.2, 0.6, 0.9], ColorSpace::Srgb, ColorSpace::Lab), [lab.l, lab.a, lab.b], 1e-3);
+
+        let linear = srgb.to_linear();
+        assert_close(convert_between(&[0.2, 0.6, 0.9], ColorSpace::Srgb, ColorSpace::LinearRgb), [linear.r, linear.g, linear.b], 1e-5);
+
+        let xyz = srgb.to_xyz::<f32>();
+        assert_close(convert_between(&[0.2, 0.6, 0.9], ColorSpace::Srgb, ColorSpace::Xyz), [xyz.x, xyz.y, xyz.z], 1e-5);
+
+        let oklab: Oklab<f32> = xyz.to_oklab();
+        assert_close(convert_between(&[lab.l, lab.a, lab.b], ColorSpace::Lab, ColorSpace::Oklab), [oklab.l, oklab.a, oklab.b], 1e-4);
+
+        let hsl: Hsl<f32, Srgb> = srgb.to_hsl();
+        assert_close(convert_between(&[0.2, 0.6, 0.9], ColorSpace::Srgb, ColorSpace::Hsl), [hsl.h.0, hsl.s, hsl.l], 1e-3);
+        let back: Rgb<f32, Srgb> = Hsl::<f32, Srgb>::new(Deg(hsl.h.0), hsl.s, hsl.l).to_rgb();
+        assert_close(convert_between(&[hsl.h.0, hsl.s, hsl.l], ColorSpace::Hsl, ColorSpace::Srgb), [back.r, back.g, back.b], 1e-5);
+
+        assert_close(convert_between(&[0., 1., 0.5], ColorSpace::Hsv, ColorSpace::Srgb), [0.5, 0., 0.], 1e-6);
+        assert_eq!(convert_between(&[1., 2., 3.], ColorSpace::Lab, ColorSpace::Lab), [1., 2., 3.]);
+    }
+
+    #[test]
+    fn test_names() {
+        for space in ColorSpace::ALL.iter() {
+            assert_eq!(space.to_string().parse(), Ok(*space));
+            assert_eq!(space.to_string().to_uppercase().parse(), Ok(*space));
+        }
+        assert_eq!("srgb".parse(), Ok(ColorSpace::Srgb));
+        assert_eq!("OkLab".parse(), Ok(ColorSpace::Oklab));
+        assert_eq!(ColorSpace::LinearRgb.to_string(), "linear-rgb");
+        assert_eq!("cmyk".parse::<ColorSpace>(), Err(ParseError::UnknownColorSpace));
+        assert_eq!("".parse::<ColorSpace>(), Err(ParseError::UnknownColorSpace));
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let srgb = [0.8, 0.3, 0.45];
+        for &space in ColorSpace::ALL.iter() {
+            let v = convert_between(&srgb, ColorSpace::Srgb, space);
+            assert_close(convert_between(&v, space, ColorSpace::Srgb), srgb, 1e-4);
+        }
+    }
+}
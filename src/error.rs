@@ -0,0 +1,56 @@
+use std::error::Error;
+use std::fmt;
+
+/// Errors returned by fallible color conversions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorError {
+    /// The color can't be represented in the target color space.
+    OutOfGamut,
+    /// The primaries of a color space don't span a volume, e.g. they are
+    /// collinear, so its conversion matrices can't be inverted.
+    DegenerateSpace,
+    /// Buffers that should be processed in pairs have different lengths.
+    LengthMismatch,
+    /// Gradient stops are empty or positioned outside of `[0, 1]`.
+    InvalidStops,
+}
+
+impl fmt::Display for ColorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColorError::OutOfGamut => write!(f, "color is out of the target gamut"),
+            ColorError::DegenerateSpace => write!(f, "color space primaries are degenerate"),
+            ColorError::LengthMismatch => write!(f, "buffers have different lengths"),
+            ColorError::InvalidStops => write!(f, "gradient stops are empty or outside of [0, 1]"),
+        }
+    }
+}
+
+impl Error for ColorError {}
+
+/// Errors returned when parsing a color from a string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The string doesn't have the number of hex digits of any supported
+    /// form, holds the number of digits found.
+    InvalidLength(usize),
+    /// The string contains a character that isn't a hex digit.
+    InvalidDigit(char),
+    /// The string is neither a hex color nor a known color name.
+    UnknownName,
+    /// The string isn't the name of a `ColorSpace`.
+    UnknownColorSpace,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidLength(len) => write!(f, "invalid hex color length {}", len),
+            ParseError::InvalidDigit(c) => write!(f, "invalid hex digit {:?}", c),
+            ParseError::UnknownName => write!(f, "not a hex color or a known color name"),
+            ParseError::UnknownColorSpace => write!(f, "unknown color space name"),
+        }
+    }
+}
+
+impl Error for ParseError {}
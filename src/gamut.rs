@@ -0,0 +1,515 @@
+//! Gamut mapping from Lab into the sRGB gamut
+
+use channel::Channel;
+use color_space::{D65, Vec3, MatrixColorSpace, Srgb, TransferFunction};
+use oklab::{Oklab, xyz_to_oklab, oklab_to_xyz};
+use error::ColorError;
+use lab::{Lab, ToLab};
+use rgb::{Rgb, ToRgb};
+use hue::normalize_radians;
+use xyz::ToXyz;
+use num_traits::{Float, NumCast};
+use std::convert::TryFrom;
+use std::f64::consts::PI;
+
+fn linear_srgb_to_oklab(rgb: [f64; 3]) -> [f64; 3] {
+    xyz_to_oklab((Srgb::to_xyz_matrix() * Vec3(rgb)).0)
+}
+
+fn oklab_to_linear_srgb(lab: [f64; 3]) -> [f64; 3] {
+    (Srgb::to_rgb_matrix() * Vec3(oklab_to_xyz(lab))).0
+}
+
+fn oklch_to_linear_srgb(l: f64, c: f64, h: f64) -> [f64; 3] {
+    oklab_to_linear_srgb([l, c * h.cos(), c * h.sin()])
+}
+
+fn delta_e_ok(lab1: [f64; 3], lab2: [f64; 3]) -> f64 {
+    ((lab1[0] - lab2[0]).powi(2) + (lab1[1] - lab2[1]).powi(2) + (lab1[2] - lab2[2]).powi(2)).sqrt()
+}
+
+fn in_gamut(rgb: [f64; 3]) -> bool {
+    const EPS: f64 = 0.000001;
+    rgb.iter().all(|c| *c >= -EPS && *c <= 1. + EPS)
+}
+
+fn clip(rgb: [f64; 3]) -> [f64; 3] {
+    [rgb[0].clamp(0., 1.), rgb[1].clamp(0., 1.), rgb[2].clamp(0., 1.)]
+}
+
+fn encode(rgb: [f64; 3]) -> Rgb<u8, Srgb> {
+    let rgb = clip(rgb);
+    Rgb::new(
+        Srgb::from_linear(rgb[0]).to_channel(),
+        Srgb::from_linear(rgb[1]).to_channel(),
+        Srgb::from_linear(rgb[2]).to_channel(),
+    )
+}
+
+// CSS Color 4 gamut mapping:
+// https://www.w3.org/TR/css-color-4/#binsearch
+fn css_gamut_map(rgb: [f64; 3]) -> [f64; 3] {
+    const JND: f64 = 0.02;
+    const EPSILON: f64 = 0.0001;
+
+    if in_gamut(rgb) {
+        return clip(rgb);
+    }
+
+    let origin = linear_srgb_to_oklab(rgb);
+    let l = origin[0];
+    if l >= 1. {
+        return [1., 1., 1.];
+    }
+    if l <= 0. {
+        return [0., 0., 0.];
+    }
+
+    let h = origin[2].atan2(origin[1]);
+    let mut current = rgb;
+    let mut clipped = clip(current);
+    let e = delta_e_ok(linear_srgb_to_oklab(clipped), origin);
+    if e < JND {
+        return clipped;
+    }
+
+    let mut min = 0.;
+    let mut max = (origin[1].powi(2) + origin[2].powi(2)).sqrt();
+    let mut min_in_gamut = true;
+    while max - min > EPSILON {
+        let chroma = (min + max) * 0.5;
+        current = oklch_to_linear_srgb(l, chroma, h);
+        if min_in_gamut && in_gamut(current) {
+            min = chroma;
+            continue;
+        }
+        clipped = clip(current);
+        let e = delta_e_ok(linear_srgb_to_oklab(clipped), linear_srgb_to_oklab(current));
+        if e < JND {
+            if JND - e < EPSILON {
+                return clipped;
+            }
+            min_in_gamut = false;
+            min = chroma;
+        }else{
+            max = chroma;
+        }
+    }
+    clipped
+}
+
+fn lab_to_linear_srgb(lab: [f64; 3]) -> [f64; 3] {
+    let xyz = Lab::<f64, D65>::new(lab[0], lab[1], lab[2]).to_xyz::<f64>();
+    (Srgb::to_rgb_matrix() * xyz.into()).0
+}
+
+// Scales down the CIELCh chroma until the color fits, keeping the Lab
+// lightness and hue
+fn chroma_reduce(lab: [f64; 3]) -> [f64; 3] {
+    const EPSILON: f64 = 0.0001;
+
+    let rgb = lab_to_linear_srgb(lab);
+    if in_gamut(rgb) {
+        return clip(rgb);
+    }
+    if lab[0] >= 100. {
+        return [1., 1., 1.];
+    }
+    if lab[0] <= 0. {
+        return [0., 0., 0.];
+    }
+
+    let mut min = 0.;
+    let mut max = 1.;
+    while max - min > EPSILON {
+        let scale = (min + max) * 0.5;
+        if in_gamut(lab_to_linear_srgb([lab[0], lab[1] * scale, lab[2] * scale])) {
+            min = scale;
+        }else{
+            max = scale;
+        }
+    }
+    clip(lab_to_linear_srgb([lab[0], lab[1] * min, lab[2] * min]))
+}
+
+/// How to bring out of gamut colors into the sRGB gamut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum GamutMapMethod {
+    /// Clamps each linear channel, cheapest but shifts hue and lightness.
+    Clip,
+    /// Reduces the CIELCh chroma, keeping the Lab lightness and hue.
+    ChromaReduce,
+    /// CSS Color 4 mapping, reduces the OkLCh chroma until clipping the
+    /// result is no longer noticeable.
+    #[default]
+    CssColor4Minde,
+}
+
+impl<T: Channel + Float + NumCast> Lab<T, D65> {
+    /// Converts to 8 bit sRGB bringing out of gamut colors into the gamut
+    /// with `method`.
+    pub fn to_srgb_with_method(&self, method: GamutMapMethod) -> Rgb<u8, Srgb> {
+        let lab = [self.l.to_channel_f64(), self.a.to_channel_f64(), self.b.to_channel_f64()];
+        let rgb = match method {
+            GamutMapMethod::Clip => clip(lab_to_linear_srgb(lab)),
+            GamutMapMethod::ChromaReduce => chroma_reduce(lab),
+            GamutMapMethod::CssColor4Minde => css_gamut_map(lab_to_linear_srgb(lab)),
+        };
+        encode(rgb)
+    }
+
+    /// Converts to 8 bit sRGB reducing the OkLCh chroma of out of gamut colors
+    /// until clipping them is no longer noticeable, preserving hue and lightness.
+    pub fn to_srgb_gamut_mapped(&self) -> Rgb<u8, Srgb> {
+        self.to_srgb_with_method(GamutMapMethod::CssColor4Minde)
+    }
+
+    /// Converts to 8 bit sRGB, failing if any channel falls outside of the
+    /// sRGB gamut by more than the 8 bit rounding error.
+    pub fn try_to_srgb(&self) -> Result<Rgb<u8, Srgb>, ColorError> {
+        let xyz = self.to_xyz::<f64>();
+        let rgb = (Srgb::to_rgb_matrix() * xyz.into()).0;
+        let half_step = 0.5 / 255.;
+        let fits = rgb.iter().all(|c| {
+            let c = Srgb::from_linear(*c);
+            c >= -half_step && c <= 1. + half_step
+        });
+        if fits {
+            Ok(encode(rgb))
+        }else{
+            Err(ColorError::OutOfGamut)
+        }
+    }
+}
+
+/// Runs a perceptual filter over an image in Lab.
+///
+/// Decodes the `width` x `height` pixels into Lab, passes the whole image to
+/// `filter` in one call as a row-major buffer of `width * height` colors and
+/// encodes its output back into sRGB, gamut mapping the colors the filter
+/// pushed out of gamut.
+///
+/// # Panics
+///
+/// If `pixels` doesn't hold `width * height` colors or the filter doesn't
+/// return one color per pixel.
+pub fn process_in_lab<F>(pixels: &[Rgb<u8, Srgb>], width: usize, height: usize, filter: F) -> Vec<Rgb<u8, Srgb>>
+where F: Fn(&[Lab<f32, D65>]) -> Vec<Lab<f32, D65>>
+{
+    assert_eq!(pixels.len(), width * height, "The image should have width * height pixels");
+    let lab: Vec<Lab<f32, D65>> = pixels.iter()
+        .map(|c| c.to_xyz::<f32>().to_lab())
+        .collect();
+    let filtered = filter(&lab);
+    assert_eq!(filtered.len(), lab.len(), "Filters must return one color per pixel");
+    filtered.iter().map(|lab| lab.to_srgb_gamut_mapped()).collect()
+}
+
+/// Histogram equalizes the L* channel of an image keeping a* and b* as they
+/// are, which enhances contrast without the hue shifts of equalizing RGB.
+pub fn equalize_lightness(pixels: &[Rgb<u8, Srgb>]) -> Vec<Rgb<u8, Srgb>> {
+    equalize_lab_lightness(pixels, f32::INFINITY)
+}
+
+/// Contrast limited version of `equalize_lightness`. Like CLAHE, every
+/// histogram bin is clipped to `clip_limit` times the mean bin count and the
+/// clipped excess is spread evenly over all bins, which bounds how much a
+/// crowded range of lightness gets stretched.
+///
+/// Limits from 256 up don't clip and give plain histogram equalization,
+/// lower ones enhance contrast more gently. Limits below 1, and NaN, are
+/// treated as 1.
+pub fn equalize_lightness_clipped(pixels: &[Rgb<u8, Srgb>], clip_limit: f32) -> Vec<Rgb<u8, Srgb>> {
+    equalize_lab_lightness(pixels, clip_limit.max(1.))
+}
+
+fn equalize_lab_lightness(pixels: &[Rgb<u8, Srgb>], clip_limit: f32) -> Vec<Rgb<u8, Srgb>> {
+    const BINS: usize = 256;
+    let bin = |l: f32| ((l / 100.).clamp(0., 1.) * (BINS - 1) as f32).round() as usize;
+    let lab: Vec<Lab<f32, D65>> = pixels.iter()
+        .map(|c| c.to_xyz::<f32>().to_lab())
+        .collect();
+    let mut histogram = [0f32; BINS];
+    for c in &lab {
+        histogram[bin(c.l)] += 1.;
+    }
+    let limit = clip_limit * lab.len() as f32 / BINS as f32;
+    let excess: f32 = histogram.iter().map(|h| (h - limit).max(0.)).sum();
+    let mut cdf = [0f32; BINS];
+    let mut total = 0.;
+    for (h, c) in histogram.iter().zip(cdf.iter_mut()) {
+        total += h.min(limit) + excess / BINS as f32;
+        *c = total;
+    }
+    // The darkest pixels map to 0
+    let cdf_min = lab.iter().map(|c| bin(c.l)).min().map_or(0., |b| cdf[b]);
+    let range = (total - cdf_min).max(1.);
+    lab.iter().map(|c| {
+        let l = 100. * (cdf[bin(c.l)] - cdf_min) / range;
+        Lab::<f32, D65>::new(l, c.a, c.b).to_srgb_gamut_mapped()
+    }).collect()
+}
+
+/// Rotates every color of the palette by the same CIELCh hue angle so its
+/// dominant hue, the chroma weighted mean hue, lands on `target_hue_deg`.
+/// Relative hues are kept and colors that end up out of gamut lose chroma.
+/// Palettes of grays are returned unchanged.
+pub fn retheme_palette(colors: &[Rgb<u8, Srgb>], target_hue_deg: f32) -> Vec<Rgb<u8, Srgb>> {
+    let (a, b) = colors.iter().fold((0., 0.), |(a, b), c| {
+        let lab: Lab<f64, D65> = c.to_xyz::<f64>().to_lab();
+        (a + lab.a, b + lab.b)
+    });
+    if a.hypot(b) < 1e-6 {
+        return colors.to_vec();
+    }
+    let delta = target_hue_deg - b.atan2(a).to_degrees() as f32;
+    colors.iter().map(|c| c.rotate_hue_in_gamut(delta)).collect()
+}
+
+impl Rgb<u8, Srgb> {
+    /// Interpolates in OkLCh taking the shortest way around the hue circle,
+    /// gamut mapping the result. Gives evenly spaced, saturated gradients.
+    pub fn lerp_oklch(&self, other: &Rgb<u8, Srgb>, t: f32) -> Rgb<u8, Srgb> {
+        const ACHROMATIC: f64 = 0.0001;
+        let lch = |c: &Rgb<u8, Srgb>| {
+            let linear = c.to_linear().to_rgb::<f64>();
+            let lab = linear_srgb_to_oklab([linear.r, linear.g, linear.b]);
+            (lab[0], lab[1].hypot(lab[2]), lab[2].atan2(lab[1]))
+        };
+        let t = t.clamp(0., 1.) as f64;
+        let (l1, c1, h1) = lch(self);
+        let (l2, c2, h2) = lch(other);
+        // Grays have no hue, take it from the other end
+        let h1 = if c1 < ACHROMATIC { h2 } else { h1 };
+        let h2 = if c2 < ACHROMATIC { h1 } else { h2 };
+        let dh = normalize_radians(h2 - h1 + PI) - PI;
+        let l = l1 + (l2 - l1) * t;
+        let c = c1 + (c2 - c1) * t;
+        let h = h1 + dh * t;
+        encode(css_gamut_map(oklch_to_linear_srgb(l, c, h)))
+    }
+
+    /// Rotates the CIELCh hue by `degrees`, reducing the chroma if the
+    /// rotated color falls out of gamut so lightness and hue are kept.
+    pub fn rotate_hue_in_gamut(&self, degrees: f32) -> Rgb<u8, Srgb> {
+        let lab: Lab<f64, D65> = self.to_xyz::<f64>().to_lab();
+        let (sin, cos) = (degrees as f64).to_radians().sin_cos();
+        let rotated = [lab.l, lab.a * cos - lab.b * sin, lab.a * sin + lab.b * cos];
+        encode(chroma_reduce(rotated))
+    }
+}
+
+impl<T: Channel + Float> Oklab<T> {
+    /// Converts to 8 bit sRGB with the CSS Color 4 gamut mapping, reducing
+    /// the OkLCh chroma of out of gamut colors until clipping them is no
+    /// longer noticeable, the way browsers render `oklab()` colors.
+    pub fn to_srgb_gamut_mapped(&self) -> Rgb<u8, Srgb> {
+        let lab = [self.l.to_channel_f64(), self.a.to_channel_f64(), self.b.to_channel_f64()];
+        encode(css_gamut_map(oklab_to_linear_srgb(lab)))
+    }
+}
+
+impl TryFrom<Lab<f32, D65>> for Rgb<u8, Srgb> {
+    type Error = ColorError;
+
+    fn try_from(lab: Lab<f32, D65>) -> Result<Rgb<u8, Srgb>, ColorError> {
+        lab.try_to_srgb()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lab::{Lab, ToLab};
+    use xyz::ToXyz;
+    use super::{process_in_lab, equalize_lightness, equalize_lightness_clipped, retheme_palette, GamutMapMethod};
+    use rgb::Rgb;
+    use color_space::{D65, Srgb};
+    use error::ColorError;
+    use oklab::{Oklab, ToOklab};
+    use std::convert::TryInto;
+    use {Color, ToHsv, ToRgb};
+
+    #[test]
+    fn test_gamut_mapped_in_gamut() {
+        let lab: Lab<f64, D65> = Rgb::<u8>::new(0x40, 0x80, 0xC0).to_xyz::<f64>().to_lab();
+        let rgb = lab.to_srgb_gamut_mapped();
+        assert!((rgb.r as i32 - 0x40).abs() <= 1, "{:?}", rgb);
+        assert!((rgb.g as i32 - 0x80).abs() <= 1, "{:?}", rgb);
+        assert!((rgb.b as i32 - 0xC0).abs() <= 1, "{:?}", rgb);
+    }
+
+    #[test]
+    fn test_gamut_mapped_preserves_hue_and_lightness() {
+        let lab = Lab::<f64, D65>::new(60., 100., -120.);
+        let rgb = lab.to_srgb_gamut_mapped();
+        let mapped: Lab<f64, D65> = rgb.to_xyz::<f64>().to_lab();
+        assert!((mapped.l - lab.l).abs() < 3., "{:?}", mapped);
+        assert!((mapped.hue() - lab.hue()).abs().to_degrees() < 10., "{:?}", mapped);
+        assert!(mapped.chromacity() < lab.chromacity());
+    }
+
+    #[test]
+    fn test_oklab_gamut_mapped() {
+        // Far outside of sRGB, oklch(0.7 0.4 150deg)
+        let h = 150f64.to_radians();
+        let lab = Oklab::new(0.7, 0.4 * h.cos(), 0.4 * h.sin());
+        let rgb = lab.to_srgb_gamut_mapped();
+        let mapped: Oklab<f64> = rgb.to_xyz::<f64>().to_oklab();
+        assert!((mapped.l - lab.l).abs() < 0.02, "{:?}", mapped);
+        let mapped_h = mapped.b.atan2(mapped.a);
+        assert!((mapped_h - h).abs().to_degrees() < 3., "{:?}", mapped);
+        assert!(mapped.a.hypot(mapped.b) < 0.4);
+
+        let gray = Oklab::new(0.5f32, 0., 0.).to_srgb_gamut_mapped();
+        assert!(gray.r == gray.g && gray.g == gray.b, "{:?}", gray);
+    }
+
+    #[test]
+    fn test_gamut_map_methods() {
+        let lab = Lab::<f32, D65>::new(60., 100., -120.);
+        let clip = lab.to_srgb_with_method(GamutMapMethod::Clip);
+        let chroma = lab.to_srgb_with_method(GamutMapMethod::ChromaReduce);
+        let css = lab.to_srgb_with_method(GamutMapMethod::CssColor4Minde);
+        assert_ne!(clip, chroma);
+        assert_ne!(clip, css);
+        assert_ne!(chroma, css);
+        assert_eq!(css, lab.to_srgb_gamut_mapped());
+
+        // chroma reduction keeps the Lab lightness, clipping doesn't
+        let lightness = |c: Rgb<u8, Srgb>| c.to_xyz::<f32>().to_lab::<f32>().l;
+        assert!((lightness(chroma) - lab.l).abs() < 1., "{:?}", chroma);
+        assert!((lightness(clip) - lab.l).abs() > 1., "{:?}", clip);
+
+        // in gamut colors are left alone by all methods
+        let c = rgb!(0x40u8, 0x80, 0xC0);
+        let lab: Lab<f32, D65> = c.to_xyz::<f32>().to_lab();
+        for method in [GamutMapMethod::Clip, GamutMapMethod::ChromaReduce, GamutMapMethod::CssColor4Minde].iter() {
+            let rgb = lab.to_srgb_with_method(*method);
+            assert!(rgb.within(&c, 1), "{:?} {:?}", method, rgb);
+        }
+    }
+
+    #[test]
+    fn test_rotate_hue_in_gamut() {
+        let c = rgb!(0x20u8, 0x60, 0xF0);
+        let lab: Lab<f64, D65> = c.to_xyz::<f64>().to_lab();
+        assert!(c.rotate_hue_in_gamut(0.).within(&c, 1));
+        assert!(c.rotate_hue_in_gamut(360.).within(&c, 1));
+        for step in 1..24 {
+            let degrees = step as f32 * 15.;
+            let rotated = c.rotate_hue_in_gamut(degrees);
+            let mapped: Lab<f64, D65> = rotated.to_xyz::<f64>().to_lab();
+            assert!((mapped.l - lab.l).abs() < 1., "{} {:?}", degrees, mapped);
+            let dh = (mapped.hue() - lab.hue()).to_degrees() - degrees as f64;
+            let dh = (dh + 540.) % 360. - 180.;
+            assert!(dh.abs() < 3., "{} {:?} {}", degrees, mapped, dh);
+            assert!(mapped.chromacity() <= lab.chromacity() + 1., "{} {:?}", degrees, mapped);
+        }
+    }
+
+    #[test]
+    fn test_retheme_palette() {
+        let palette = [rgb!(0x20u8, 0x40, 0xC0), rgb!(0x40, 0x60, 0xE0), rgb!(0x50, 0x70, 0xA0), rgb!(0x20, 0x30, 0x80)];
+        let hue = |c: &Rgb<u8>| { let lab: Lab<f64, D65> = c.to_xyz::<f64>().to_lab(); lab.hue().to_degrees() };
+        let rethemed = retheme_palette(&palette, 30.);
+        assert_eq!(rethemed.len(), palette.len());
+        let shifts: Vec<f64> = palette.iter().zip(rethemed.iter())
+            .map(|(a, b)| (hue(b) - hue(a) + 360.) % 360.)
+            .collect();
+        for shift in shifts.iter() {
+            assert!((shift - shifts[0]).abs() < 4., "{:?}", shifts);
+        }
+        for c in rethemed.iter() {
+            // every color is now reddish
+            assert!(c.r > c.b, "{:?}", rethemed);
+        }
+
+        let grays = [rgb!(0x10u8, 0x10, 0x10), rgb!(0x80, 0x80, 0x80)];
+        assert_eq!(retheme_palette(&grays, 30.), grays.to_vec());
+    }
+
+    #[test]
+    fn test_try_into_srgb() {
+        for c in [rgb!(0x40u8, 0x80, 0xC0), rgb!(0, 0, 0), rgb!(0xFF, 0xFF, 0xFF), rgb!(0xFF, 0, 0)].iter() {
+            let lab: Lab<f32, D65> = c.to_xyz::<f32>().to_lab();
+            let rgb: Rgb<u8, Srgb> = lab.try_into().unwrap();
+            assert_eq!(rgb, *c);
+        }
+        let lab = Lab::<f32, D65>::new(60., 100., -120.);
+        let rgb: Result<Rgb<u8, Srgb>, _> = lab.try_into();
+        assert_eq!(rgb, Err(ColorError::OutOfGamut));
+    }
+
+    #[test]
+    fn test_lerp_oklch() {
+        let red = rgb!(0xFFu8, 0, 0);
+        let blue = rgb!(0u8, 0, 0xFF);
+        assert_eq!(red.lerp_oklch(&blue, 0.), red);
+        assert_eq!(red.lerp_oklch(&blue, 1.), blue);
+
+        let mid = red.lerp_oklch(&blue, 0.5);
+        let linear_mid = red.to_rgb::<f32>().to_linear().mix(blue.to_rgb::<f32>().to_linear(), 0.5).encode_srgb().to_rgb::<u8>();
+        let hue = |c: Rgb<u8, Srgb>| c.to_hsv::<f32>().h.0;
+        assert!(mid.chroma() > 0x80, "{:?}", mid);
+        assert!(hue(mid) > 240. && hue(mid) < 360., "{:?}", mid);
+        let mid_lab: Lab<f32, D65> = mid.to_xyz::<f32>().to_lab();
+        let linear_lab: Lab<f32, D65> = linear_mid.to_xyz::<f32>().to_lab();
+        assert!(mid_lab.chromacity() > linear_lab.chromacity(), "{:?} {:?}", mid, linear_mid);
+    }
+
+    #[test]
+    fn test_process_in_lab() {
+        let pixels: Vec<Rgb<u8, Srgb>> = (0..12u8)
+            .map(|i| rgb!(i * 21, 0xFF - i * 17, ((i as u32 * 97) % 0xFF) as u8))
+            .collect();
+        let identity = process_in_lab(&pixels, 4, 3, |lab| lab.to_vec());
+        assert_eq!(identity, pixels);
+
+        let darker = process_in_lab(&pixels, 4, 3, |lab| {
+            lab.iter().map(|c| Lab::new(c.l * 0.5, c.a, c.b)).collect()
+        });
+        for (d, p) in darker.iter().zip(pixels.iter()) {
+            assert!(d.max_channel() <= p.max_channel(), "{:?} {:?}", d, p);
+        }
+    }
+
+    #[test]
+    fn test_equalize_lightness() {
+        // Low contrast image of grays and muted reds between L* 40 and 60
+        let image: Vec<Rgb<u8, Srgb>> = (0..64u8)
+            .map(|i| if i % 4 == 0 { rgb!(0x70 + i / 2, 0x60, 0x60) } else { rgb!(0x60 + i / 2, 0x60 + i / 2, 0x60 + i / 2) })
+            .collect();
+        let histogram = |pixels: &[Rgb<u8, Srgb>]| {
+            let mut bins = [0; 10];
+            for c in pixels {
+                let lab: Lab<f32, D65> = c.to_xyz::<f32>().to_lab();
+                bins[((lab.l / 10.) as usize).min(9)] += 1;
+            }
+            bins
+        };
+        let equalized = equalize_lightness(&image);
+        assert_eq!(equalized.len(), image.len());
+        let before = histogram(&image);
+        let after = histogram(&equalized);
+        assert!(after.iter().max() < before.iter().max(), "{:?} {:?}", before, after);
+        assert!(after.iter().filter(|b| **b > 0).count() > 5, "{:?}", after);
+
+        // Clipping limits the stretch, the clipped result spans less of L*
+        let spread = |pixels: &[Rgb<u8, Srgb>]| {
+            let l: Vec<f32> = pixels.iter().map(|c| c.to_xyz::<f32>().to_lab::<f32>().l).collect();
+            l.iter().cloned().fold(0f32, f32::max) - l.iter().cloned().fold(100f32, f32::min)
+        };
+        let clipped = equalize_lightness_clipped(&image, 4.);
+        assert!(spread(&image) < spread(&clipped) && spread(&clipped) < spread(&equalized),
+            "{} {} {}", spread(&image), spread(&clipped), spread(&equalized));
+        assert_eq!(equalize_lightness_clipped(&image, 256.), equalized);
+        assert_eq!(equalize_lightness_clipped(&image, f32::NAN), equalize_lightness_clipped(&image, 0.5));
+
+        // Grays stay gray
+        for (c, e) in image.iter().zip(equalized.iter()) {
+            if c.chroma() == 0 {
+                assert!(e.chroma() <= 1, "{:?}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Gradients interpolating between colors, with stops and baked lookup tables
+
+use Color;
+use channel::Channel;
+use color_space::Srgb;
+use rgb::{Rgb, ToRgb};
+use error::ColorError;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Gradient<C> {
+    pub start: C,
+    pub end: C,
+}
+
+impl<C: Copy> Gradient<C> {
+    pub fn new(start: C, end: C) -> Gradient<C> {
+        Gradient { start, end }
+    }
+
+    /// Color at `t` in `[0, 1]`, interpolated in the space of `C`.
+    pub fn at<T: Channel>(&self, t: f32) -> C
+    where C: Color<T>
+    {
+        let t = t.clamp(0., 1.);
+        self.start.mix(self.end, t.to_channel())
+    }
+
+    /// `n` evenly spaced colors from `start` to `end`, both included.
+    pub fn take<T: Channel>(&self, n: usize) -> Vec<C>
+    where C: Color<T>
+    {
+        self.steps(n).collect()
+    }
+
+    /// Iterator over `n` evenly spaced colors from `start` to `end`, both
+    /// included, computed lazily.
+    pub fn steps<T: Channel>(&self, n: usize) -> impl Iterator<Item = C> + '_
+    where C: Color<T>
+    {
+        assert!(n >= 2, "A gradient needs at least 2 steps");
+        let last = n - 1;
+        (0..n).map(move |i| match i {
+            0 => self.start,
+            i if i == last => self.end,
+            i => self.at::<T>(i as f32 / last as f32),
+        })
+    }
+
+    /// Precomputes `resolution` evenly spaced samples for fast lookups.
+    pub fn bake<T: Channel>(&self, resolution: usize) -> BakedGradient
+    where C: Color<T> + ToRgb
+    {
+        assert!(resolution >= 2, "A baked gradient needs at least 2 samples");
+        let last = (resolution - 1) as f32;
+        let lut = (0..resolution)
+            .map(|i| self.at::<T>(i as f32 / last).to_rgb::<f32>().to_standard::<Srgb>().to_rgb())
+            .collect();
+        BakedGradient { lut }
+    }
+}
+
+/// Gradient through any number of colors at positions in `[0, 1]`, like a
+/// colormap.
+#[derive(Clone, Debug)]
+pub struct GradientStops<C> {
+    stops: Vec<(f32, C)>,
+}
+
+impl<C: Copy> GradientStops<C> {
+    /// Sorts the stops by position. Fails if there are none or any position
+    /// is outside of `[0, 1]`.
+    pub fn new(mut stops: Vec<(f32, C)>) -> Result<GradientStops<C>, ColorError> {
+        if stops.is_empty() || !stops.iter().all(|(p, _)| (0. ..=1.).contains(p)) {
+            return Err(ColorError::InvalidStops);
+        }
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(GradientStops { stops })
+    }
+
+    /// Color at `t`, mixed between the two stops around it. Before the first
+    /// or after the last stop it's the color of that stop.
+    pub fn sample<T: Channel>(&self, t: f32) -> C
+    where C: Color<T>
+    {
+        let next = self.stops.iter().position(|(p, _)| *p >= t);
+        match next {
+            None => self.stops[self.stops.len() - 1].1,
+            Some(0) => self.stops[0].1,
+            Some(i) => {
+                let (p0, c0) = self.stops[i - 1];
+                let (p1, c1) = self.stops[i];
+                c0.mix(c1, ((t - p0) / (p1 - p0)).to_channel())
+            }
+        }
+    }
+
+    pub fn stops(&self) -> &[(f32, C)] {
+        &self.stops
+    }
+}
+
+/// Gradient sampled into a lookup table of 8 bit sRGB colors.
+#[derive(Clone, Debug)]
+pub struct BakedGradient {
+    lut: Vec<Rgb<u8, Srgb>>,
+}
+
+impl BakedGradient {
+    /// Nearest precomputed color to `t` in `[0, 1]`.
+    #[inline]
+    pub fn sample(&self, t: f32) -> Rgb<u8, Srgb> {
+        let last = self.lut.len() - 1;
+        let i = (t.clamp(0., 1.) * last as f32).round() as usize;
+        self.lut[i]
+    }
+
+    pub fn lut(&self) -> &[Rgb<u8, Srgb>] {
+        &self.lut
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gradient, GradientStops};
+    use error::ColorError;
+    use {Lab, Rgb, ToRgb};
+    use color_space::{D65, LinearRgb};
+
+    #[test]
+    fn test_baked_matches_at() {
+        let gradient = Gradient::new(rgb!(1.0f32, 0.0, 0.0), rgb!(0.0f32, 0.2, 1.0));
+        let baked = gradient.bake(256);
+        for i in 0..=100 {
+            let t = i as f32 / 100.;
+            let expected = gradient.at(t).to_rgb::<u8>();
+            let sampled = baked.sample(t);
+            assert!((expected.r as i32 - sampled.r as i32).abs() <= 1, "{} {:?} {:?}", t, expected, sampled);
+            assert!((expected.g as i32 - sampled.g as i32).abs() <= 1, "{} {:?} {:?}", t, expected, sampled);
+            assert!((expected.b as i32 - sampled.b as i32).abs() <= 1, "{} {:?} {:?}", t, expected, sampled);
+        }
+        assert_eq!(baked.sample(-1.), rgb!(0xFF, 0, 0));
+        assert_eq!(baked.sample(2.), rgb!(0, 0x33, 0xFF));
+    }
+
+    #[test]
+    fn test_take() {
+        let gradient = Gradient::new(Lab::<f32, D65>::new(0., 0., 0.), Lab::new(100., 0., 0.));
+        let stops = gradient.take(5);
+        assert_eq!(stops.len(), 5);
+        assert_eq!(stops[0].l, 0.);
+        assert_eq!(stops[4].l, 100.);
+        assert!(stops.windows(2).all(|w| w[0].l < w[1].l), "{:?}", stops);
+        assert_eq!(stops[2].l, 50.);
+
+        let gradient = Gradient::new(rgb!(1.0f32, 0.0, 0.0), rgb!(0.0f32, 0.2, 1.0));
+        let steps: Vec<_> = gradient.steps(3).collect();
+        assert_eq!(steps, vec![gradient.start, gradient.at(0.5), gradient.end]);
+        assert_eq!(gradient.steps(2).count(), 2);
+    }
+
+    #[test]
+    fn test_gradient_stops() {
+        let blue = rgb!(0f32, 0., 1.);
+        let white = rgb!(1f32, 1., 1.);
+        let red = rgb!(1f32, 0., 0.);
+        let map = GradientStops::new(vec![(1., red), (0., blue), (0.5, white)]).unwrap();
+        assert_eq!(map.sample(0.), blue);
+        assert_eq!(map.sample(0.25), rgb!(0.5, 0.5, 1.));
+        assert_eq!(map.sample(0.5), white);
+        assert_eq!(map.sample(0.75), rgb!(1., 0.5, 0.5));
+        assert_eq!(map.sample(1.), red);
+        assert_eq!(map.sample(-1.), blue);
+        assert_eq!(map.sample(2.), red);
+
+        assert_eq!(GradientStops::new(vec![(0.2, red)]).unwrap().sample(0.9), red);
+        assert_eq!(GradientStops::new(vec![(0., blue), (1.5, red)]).unwrap_err(), ColorError::InvalidStops);
+        assert_eq!(GradientStops::new(vec![(-0.1, blue)]).unwrap_err(), ColorError::InvalidStops);
+        assert_eq!(GradientStops::new(vec![(f32::NAN, blue)]).unwrap_err(), ColorError::InvalidStops);
+        assert_eq!(GradientStops::<Rgb<f32>>::new(vec![]).unwrap_err(), ColorError::InvalidStops);
+    }
+
+    #[test]
+    fn test_baked_interpolates_in_gradient_space() {
+        let gradient = Gradient::new(
+            Rgb::<f32, LinearRgb>::new(0., 0., 0.),
+            Rgb::<f32, LinearRgb>::new(1., 1., 1.),
+        );
+        let baked = gradient.bake(3);
+        // linear 0.5 encodes to sRGB 0.735
+        assert_eq!(baked.sample(0.5), rgb!(0xBC, 0xBC, 0xBC));
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright 2013 The color-rs developers. For a full listing of the authors,
+// refer to the AUTHORS file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use num_traits::{self, NumCast, Num};
+use angle::*;
+
+use {Channel, FloatChannel};
+use {Rgb, ToRgb};
+use color_space::{Srgb, TransferFunction};
+use hue::{normalize_degrees, fmt_hue, fmt_percent};
+use std::marker::PhantomData;
+use std::fmt;
+
+#[inline]
+fn cast<T: num_traits::NumCast, U: num_traits::NumCast>(n: T) -> U {
+    num_traits::cast(n).unwrap()
+}
+
+/// Hue, saturation and lightness of gamma encoded RGB, as used by CSS.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Hsl<T = f32, S = Srgb> { pub h: Deg<T>, pub s: T, pub l: T, pub standard: PhantomData<S> }
+
+impl<T: Clone,S> Clone for Hsl<T, S>{
+    fn clone(&self) -> Hsl<T, S>{
+        Hsl{ h: self.h.clone(), s: self.s.clone(), l: self.l.clone(), standard: PhantomData }
+    }
+}
+
+impl<T: Copy, S> Copy for Hsl<T, S>{}
+
+impl<N: Clone + PartialEq + Num + NumCast, S> PartialEq for Hsl<N, S>{
+    #[inline]
+    fn eq(&self, other: &Hsl<N, S>) -> bool{
+        self.h.clone().wrap().eq(&other.h.clone().wrap()) && self.s.eq(&other.s) && self.l.eq(&other.l)
+    }
+}
+
+impl<T, S> Hsl<T, S> {
+    pub const fn new(h: Deg<T>, s: T, l: T) -> Hsl<T, S> {
+        Hsl { h, s, l, standard: PhantomData }
+    }
+}
+
+impl<T: FloatChannel, S> Hsl<T, S> {
+    /// Copy with the hue replaced, wrapped to `[0, 360)` degrees.
+    #[inline]
+    pub fn with_hue(&self, h: T) -> Hsl<T, S> {
+        Hsl::new(Deg(normalize_degrees(h)), self.s, self.l)
+    }
+
+    /// Copy with the saturation replaced, clamped to `[0, 1]`.
+    #[inline]
+    pub fn with_saturation(&self, s: T) -> Hsl<T, S> {
+        Hsl::new(self.h, s.saturate(), self.l)
+    }
+
+    /// Copy with the lightness replaced, clamped to `[0, 1]`.
+    #[inline]
+    pub fn with_lightness(&self, l: T) -> Hsl<T, S> {
+        Hsl::new(self.h, self.s, l.saturate())
+    }
+}
+
+/// Formats as CSS `hsl(120 50% 50%)`.
+impl<T: Channel + NumCast, S> fmt::Display for Hsl<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "hsl({} {}% {}%)", fmt_hue(self.h.0), fmt_percent(self.s), fmt_percent(self.l))
+    }
+}
+
+pub trait ToHsl {
+    type Standard: TransferFunction;
+    fn to_hsl<U: FloatChannel>(&self) -> Hsl<U, Self::Standard>;
+}
+
+impl<T: FloatChannel, S: TransferFunction> ToHsl for Hsl<T, S> {
+    type Standard = S;
+    #[inline]
+    fn to_hsl<U: FloatChannel>(&self) -> Hsl<U, S> {
+        Hsl::new(Deg(cast(self.h.0)), self.s.to_channel(), self.l.to_channel())
+    }
+}
+
+impl<T: Channel, S: TransferFunction> ToHsl for Rgb<T, S> {
+    type Standard = S;
+    fn to_hsl<U: FloatChannel>(&self) -> Hsl<U, S> {
+        let r = self.r.to_channel_f64();
+        let g = self.g.to_channel_f64();
+        let b = self.b.to_channel_f64();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.;
+        let d = max - min;
+        if d == 0. {
+            return Hsl::new(Deg(cast(0.)), cast(0.), cast(l));
+        }
+
+        let s = if l > 0.5 { d / (2. - max - min) } else { d / (max + min) };
+        let h = if max == r {
+            (g - b) / d + if g < b { 6. } else { 0. }
+        } else if max == g {
+            (b - r) / d + 2.
+        } else {
+            (r - g) / d + 4.
+        };
+        Hsl::new(Deg(cast(h * 60.)), cast(s), cast(l))
+    }
+}
+
+impl<T: FloatChannel, S: TransferFunction> ToRgb for Hsl<T, S> {
+    type Standard = S;
+    fn to_rgb<U: Channel>(&self) -> Rgb<U, S> {
+        let h = normalize_degrees(self.h.0.to_channel_f64());
+        let s = self.s.to_channel_f64();
+        let l = self.l.to_channel_f64();
+        let a = s * l.min(1. - l);
+        let f = |n: f64| {
+            let k = (n + h / 30.) % 12.;
+            l - a * (k - 3.).min(9. - k).clamp(-1., 1.)
+        };
+        Rgb::new(f(0.).to_channel(), f(8.).to_channel(), f(4.).to_channel())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {Hsl, ToHsl};
+    use {Rgb, ToRgb};
+    use angle::*;
+
+    #[test]
+    fn test_hsl_to_rgb() {
+        // examples from the CSS Color 4 spec
+        let colors = [
+            (Hsl::<f32>::new(Deg(0.), 1., 0.5), Rgb::<u8>::new(0xFF, 0x00, 0x00)),
+            (Hsl::<f32>::new(Deg(60.), 1., 0.5), Rgb::<u8>::new(0xFF, 0xFF, 0x00)),
+            (Hsl::<f32>::new(Deg(120.), 1., 0.25), Rgb::<u8>::new(0x00, 0x80, 0x00)),
+            (Hsl::<f32>::new(Deg(120.), 1., 0.75), Rgb::<u8>::new(0x80, 0xFF, 0x80)),
+            (Hsl::<f32>::new(Deg(120.), 0.75, 0.75), Rgb::<u8>::new(0x8F, 0xEF, 0x8F)),
+            (Hsl::<f32>::new(Deg(240.), 1., 0.5), Rgb::<u8>::new(0x00, 0x00, 0xFF)),
+            (Hsl::<f32>::new(Deg(0.), 0., 0.5), Rgb::<u8>::new(0x80, 0x80, 0x80)),
+        ];
+        for &(hsl, rgb) in colors.iter() {
+            assert_eq!(hsl.to_rgb::<u8>(), rgb, "{}", hsl);
+        }
+    }
+
+    #[test]
+    fn test_hsl_round_trip() {
+        let colors = [
+            Rgb::<f32>::new(0., 0., 0.),
+            Rgb::<f32>::new(1., 1., 1.),
+            Rgb::<f32>::new(1., 0., 0.),
+            Rgb::<f32>::new(0., 1., 0.),
+            Rgb::<f32>::new(0., 0., 1.),
+            Rgb::<f32>::new(1., 1., 0.),
+            Rgb::<f32>::new(0., 1., 1.),
+            Rgb::<f32>::new(1., 0., 1.),
+        ];
+        for rgb in colors.iter() {
+            let hsl = rgb.to_hsl::<f32>();
+            assert_eq!(hsl.to_rgb::<f32>(), *rgb, "{}", hsl);
+        }
+        assert_eq!(Rgb::<f32>::new(0., 0., 1.).to_hsl::<f32>(), Hsl::new(Deg(240.), 1., 0.5));
+        assert_eq!(Rgb::<f32>::new(1., 1., 1.).to_hsl::<f32>(), Hsl::new(Deg(0.), 0., 1.));
+    }
+
+    #[test]
+    fn test_with_display() {
+        let hsl = Hsl::<f32>::new(Deg(120.), 0.5, 0.25);
+        assert_eq!(hsl.to_string(), "hsl(120 50% 25%)");
+        assert_eq!(hsl.with_hue(-30.).to_string(), "hsl(330 50% 25%)");
+        assert_eq!(hsl.with_saturation(1.5).to_string(), "hsl(120 100% 25%)");
+        assert_eq!(hsl.with_lightness(0.6).to_string(), "hsl(120 50% 60%)");
+    }
+
+    #[test]
+    fn test_with_lightness() {
+        let hsl = Hsl::<f32>::new(Deg(120.), 0.5, 0.25);
+        assert_eq!(hsl.with_lightness(0.75), Hsl::new(Deg(120.), 0.5, 0.75));
+        assert_eq!(hsl.with_lightness(1.5), Hsl::new(Deg(120.), 0.5, 1.));
+        assert_eq!(hsl.with_lightness(-0.2), Hsl::new(Deg(120.), 0.5, 0.));
+        assert_eq!(hsl.with_saturation(-0.5), Hsl::new(Deg(120.), 0., 0.25));
+        assert_eq!(hsl.with_hue(400.), Hsl::new(Deg(40.), 0.5, 0.25));
+    }
+}
@@ -19,9 +19,11 @@ use angle::*;
 use {Color, FloatColor};
 use {Channel, FloatChannel};
 use {Rgb, ToRgb};
-use alpha::{ToRgba, Rgba};
+use alpha::{ToRgba, Rgba, Hsva};
 use color_space::{Srgb, TransferFunction};
+use hue::{normalize_degrees, fmt_hue, fmt_percent, fmt_alpha};
 use std::marker::PhantomData;
+use std::fmt;
 
 #[inline]
 fn cast<T: num_traits::NumCast, U: num_traits::NumCast>(n: T) -> U {
@@ -54,6 +56,42 @@ impl<T, S> Hsv<T, S> {
     }
 }
 
+impl<T: FloatChannel, S> Hsv<T, S> {
+    /// Copy with the hue replaced, wrapped to `[0, 360)` degrees.
+    #[inline]
+    pub fn with_hue(&self, h: T) -> Hsv<T, S> {
+        Hsv::new(Deg(normalize_degrees(h)), self.s, self.v)
+    }
+
+    /// Copy with the saturation replaced, clamped to `[0, 1]`.
+    #[inline]
+    pub fn with_saturation(&self, s: T) -> Hsv<T, S> {
+        Hsv::new(self.h, s.saturate(), self.v)
+    }
+
+    /// Copy with the value replaced, clamped to `[0, 1]`.
+    #[inline]
+    pub fn with_value(&self, v: T) -> Hsv<T, S> {
+        Hsv::new(self.h, self.s, v.saturate())
+    }
+}
+
+/// Formats as `hsv(120 50% 50%)`. This mimics CSS syntax but isn't valid CSS,
+/// CSS has no HSV function.
+impl<T: Channel + NumCast, S> fmt::Display for Hsv<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "hsv({} {}% {}%)", fmt_hue(self.h.0), fmt_percent(self.s), fmt_percent(self.v))
+    }
+}
+
+/// Formats as `hsv(120 50% 50% / 0.5)`, see `Hsv`.
+impl<T: Channel + NumCast, S> fmt::Display for Hsva<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "hsv({} {}% {}% / {})",
+            fmt_hue(self.c.h.0), fmt_percent(self.c.s), fmt_percent(self.c.v), fmt_alpha(self.a))
+    }
+}
+
 impl<T: Channel + NumCast + Num, S: TransferFunction> Color<T> for Hsv<T, S> {
     /// Clamps the components of the color to the range `(lo,hi)`.
     #[inline]
@@ -90,7 +128,7 @@ impl<T: FloatChannel> FloatColor<T> for Hsv<T> {
     /// `h` component, and `s` and `v` are clamped to the range `(0,1)`.
     #[inline]
     fn saturate(self) -> Hsv<T> {
-        Hsv::new(self.h.wrap(),
+        Hsv::new(Deg(normalize_degrees(self.h.value())),
                  self.s.saturate(),
                  self.v.saturate())
     }
@@ -145,7 +183,7 @@ impl<T:Clone + Channel + NumCast + Num, S: TransferFunction> ToRgb for Hsv<T, S>
             Rgb::new(gray, gray, gray)
         } else {
             let max_f: f64 = cast(T::max());
-            let hue: f64 = cast(self.h.wrap().value());
+            let hue: f64 = normalize_degrees(cast(self.h.value()));
             let hue_six: f64 = hue / 360f64 * 6f64;
             let hue_six_cat: usize = cast(hue_six);
             let hue_six_rem: T = cast(hue_six.fract() * max_f);
@@ -171,6 +209,27 @@ mod tests {
     use {Hsv, ToHsv};
     use {Rgb, ToRgb};
     use angle::*;
+    use alpha::Hsva;
+
+    #[test]
+    fn test_with() {
+        let hsv = Hsv::<f32>::new(Deg(120.), 0.5, 0.25);
+        assert_eq!(hsv.with_value(0.75), Hsv::new(Deg(120.), 0.5, 0.75));
+        assert_eq!(hsv.with_value(1.5), Hsv::new(Deg(120.), 0.5, 1.));
+        assert_eq!(hsv.with_saturation(-0.5), Hsv::new(Deg(120.), 0., 0.25));
+        assert_eq!(hsv.with_hue(-90.), Hsv::new(Deg(270.), 0.5, 0.25));
+        assert_eq!(hsv.with_hue(400.).h.0, 40.);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Hsv::<f32>::new(Deg(120.), 0.5, 0.5).to_string(), "hsv(120 50% 50%)");
+        assert_eq!(Hsv::<f32>::new(Deg(89.6), 0.125, 1.).to_string(), "hsv(90 12.5% 100%)");
+        assert_eq!(Hsv::<f64>::new(Deg(-30.2), 0.3333, 0.).to_string(), "hsv(330 33.3% 0%)");
+        assert_eq!(Hsv::<f32>::new(Deg(359.7), 1., 1.).to_string(), "hsv(0 100% 100%)");
+        let hsva = Hsva { c: Hsv::<f32>::new(Deg(200.), 0.75, 0.25), a: 0.5 };
+        assert_eq!(hsva.to_string(), "hsv(200 75% 25% / 0.5)");
+    }
 
     #[test]
     fn test_hsv_to_hsv() {
@@ -191,4 +250,28 @@ mod tests {
         assert_eq!(Hsv::<u16>::new(Deg(120), 65535, 39321).to_rgb::<u8>(), Rgb::<u8>::new(0x00, 0x99, 0x00));
         assert_eq!(Hsv::<u16>::new(Deg(240), 65535, 39321).to_rgb::<u8>(), Rgb::<u8>::new(0x00, 0x00, 0x99));
     }
+
+    #[test]
+    fn test_hsv_rgb_round_trip() {
+        // pure red, 50% gray and a desaturated pastel pink
+        let colors = [
+            (Rgb::<u8>::new(0xFF, 0x00, 0x00), Hsv::<f32>::new(Deg(0.), 1., 1.)),
+            (Rgb::<u8>::new(0x80, 0x80, 0x80), Hsv::<f32>::new(Deg(0.), 0., 128. / 255.)),
+            (Rgb::<u8>::new(0xFF, 0xCC, 0xDD), Hsv::<f32>::new(Deg(340.), 0.2, 1.)),
+        ];
+        for &(rgb, hsv) in colors.iter() {
+            let to_hsv = rgb.to_hsv::<f32>();
+            assert!((to_hsv.h.0 - hsv.h.0).abs() < 0.5, "{:?} {:?}", to_hsv, hsv);
+            assert!((to_hsv.s - hsv.s).abs() < 1e-3, "{:?} {:?}", to_hsv, hsv);
+            assert!((to_hsv.v - hsv.v).abs() < 1e-3, "{:?} {:?}", to_hsv, hsv);
+            assert_eq!(hsv.to_rgb::<u8>(), rgb);
+            assert_eq!(to_hsv.to_rgb::<u8>(), rgb);
+        }
+
+        // hue wraps around at 360
+        assert_eq!(Hsv::<f32>::new(Deg(360.), 1., 1.).to_rgb::<u8>(), Rgb::<u8>::new(0xFF, 0x00, 0x00));
+        assert_eq!(Hsv::<f32>::new(Deg(-120.), 1., 1.).to_rgb::<u8>(), Rgb::<u8>::new(0x00, 0x00, 0xFF));
+        // achromatic colors ignore the hue
+        assert_eq!(Hsv::<f32>::new(Deg(200.), 0., 0.5).to_rgb::<u8>(), Rgb::<u8>::new(0x80, 0x80, 0x80));
+    }
 }
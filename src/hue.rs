@@ -0,0 +1,65 @@
+//! Hue angle helpers shared by the cylindrical color types, and the number
+//! formatting of their `Display` impls
+
+use channel::Channel;
+use num_traits::{Float, NumCast, cast, zero};
+
+fn normalize<T: Float>(angle: T, full_turn: T) -> T {
+    let r = angle % full_turn;
+    let r = if r < zero() { r + full_turn } else { r };
+    // A tiny negative angle can round up to a full turn
+    if r >= full_turn { zero() } else { r }
+}
+
+/// Wraps an angle in degrees to `[0, 360)`.
+#[inline]
+pub fn normalize_degrees<T: Float>(degrees: T) -> T {
+    normalize(degrees, cast(360.).unwrap())
+}
+
+/// Wraps an angle in radians to `[0, 2π)`.
+#[inline]
+pub fn normalize_radians<T: Float>(radians: T) -> T {
+    normalize(radians, cast(std::f64::consts::TAU).unwrap())
+}
+
+// Hue rounded to whole degrees in [0, 360)
+pub(crate) fn fmt_hue<T: NumCast>(h: T) -> f32 {
+    normalize_degrees(cast::<T, f32>(h).unwrap().round())
+}
+
+// Percentage with at most one decimal
+pub(crate) fn fmt_percent<T: Channel>(c: T) -> f32 {
+    (c.to_channel_f32() * 1000.).round() / 10.
+}
+
+// Alpha with at most three decimals
+pub(crate) fn fmt_alpha<T: Channel>(a: T) -> f32 {
+    (a.to_channel_f32() * 1000.).round() / 1000.
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_degrees, normalize_radians};
+    use std::f64::consts::{PI, TAU};
+
+    #[test]
+    fn test_normalize_degrees() {
+        assert_eq!(normalize_degrees(-30f32), 330.);
+        assert_eq!(normalize_degrees(720f32), 0.);
+        assert_eq!(normalize_degrees(360f64), 0.);
+        assert_eq!(normalize_degrees(-750f64), 330.);
+        assert_eq!(normalize_degrees(0f32), 0.);
+        assert_eq!(normalize_degrees(123.5f32), 123.5);
+        assert_eq!(normalize_degrees(359.9f64), 359.9);
+        assert_eq!(normalize_degrees(-1e-20f64), 0.);
+    }
+
+    #[test]
+    fn test_normalize_radians() {
+        assert!((normalize_radians(-PI / 2.) - 3. * PI / 2.).abs() < 1e-12);
+        assert!(normalize_radians(2. * TAU).abs() < 1e-12);
+        assert_eq!(normalize_radians(1f64), 1.);
+        assert_eq!(normalize_radians(-1e-20f64), 0.);
+    }
+}
@@ -1,9 +1,19 @@
+use Color;
 use channel::Channel;
-use color_space::{WhitePoint};
-use num_traits::{Float, NumCast, cast, zero};
+use color_space::{WhitePoint, D65, Srgb};
+use error::ColorError;
+use rgb::{Rgb, ToRgb};
+use num_traits::{Float, NumCast, cast};
+use hue::{normalize_degrees, normalize_radians};
 use xyz::{Xyz, ToXyz};
 use std::ops::{Add, Mul};
+use serde::{Serialize, Serializer, Deserialize, Deserializer, de};
 
+/// CIE L\*a\*b\* relative to the white point `Wp`.
+///
+/// 8 bit sRGB converted to `Lab<f32, D65>` and back is guaranteed to be
+/// within one level per channel of the original, in practice it comes back
+/// unchanged.
 #[derive(Clone, Copy, Debug)]
 pub struct Lab<T, Wp>{
     pub l: T,
@@ -31,12 +41,7 @@ impl<T: Float, Wp: WhitePoint> Lab<T, Wp>{
     }
 
     pub fn hue(&self) -> T {
-        let h = self.b.atan2(self.a);
-        if h < zero() {
-            h + cast(std::f64::consts::TAU).unwrap()
-        }else{
-            h
-        }
+        normalize_radians(self.b.atan2(self.a))
     }
 
     pub fn offset_chromacity(&self, chroma_offset: T) -> Lab<T, Wp>{
@@ -49,6 +54,233 @@ impl<T: Float, Wp: WhitePoint> Lab<T, Wp>{
             self.b + offset_b,
         )
     }
+
+    /// CIE76 color difference, the euclidean distance in Lab. The cheapest
+    /// metric but overstates differences between saturated colors.
+    pub fn delta_e_76(&self, other: &Lab<T, Wp>) -> T {
+        ((self.l - other.l).powi(2) + (self.a - other.a).powi(2) + (self.b - other.b).powi(2)).sqrt()
+    }
+
+    /// CIE94 color difference with the graphic arts weights. `self` is the
+    /// reference color so the metric isn't symmetric.
+    pub fn delta_e_94(&self, other: &Lab<T, Wp>) -> T {
+        let k1: T = cast(0.045).unwrap();
+        let k2: T = cast(0.015).unwrap();
+        let c1 = self.chromacity();
+        let c2 = other.chromacity();
+        let dl = self.l - other.l;
+        let dc = c1 - c2;
+        let da = self.a - other.a;
+        let db = self.b - other.b;
+        let dh2 = (da * da + db * db - dc * dc).max(T::zero());
+        let sc = T::one() + k1 * c1;
+        let sh = T::one() + k2 * c1;
+        (dl * dl + (dc / sc).powi(2) + dh2 / (sh * sh)).sqrt()
+    }
+
+    /// CIEDE2000 color difference, around 1 for a just noticeable difference.
+    /// See http://www2.ece.rochester.edu/~gsharma/ciede2000/
+    pub fn delta_e_2000(&self, other: &Lab<T, Wp>) -> T {
+        let f = |v: T| v.to_f64().unwrap();
+        let (l1, a1, b1) = (f(self.l), f(self.a), f(self.b));
+        let (l2, a2, b2) = (f(other.l), f(other.a), f(other.b));
+        let pow25_7 = 25f64.powi(7);
+
+        let c_bar = (a1.hypot(b1) + a2.hypot(b2)) / 2.;
+        let g = 0.5 * (1. - (c_bar.powi(7) / (c_bar.powi(7) + pow25_7)).sqrt());
+        let a1p = (1. + g) * a1;
+        let a2p = (1. + g) * a2;
+        let c1p = a1p.hypot(b1);
+        let c2p = a2p.hypot(b2);
+        let hue = |b: f64, a: f64| if a == 0. && b == 0. { 0. } else { normalize_degrees(b.atan2(a).to_degrees()) };
+        let h1p = hue(b1, a1p);
+        let h2p = hue(b2, a2p);
+        let achromatic = c1p * c2p == 0.;
+
+        let dl = l2 - l1;
+        let dc = c2p - c1p;
+        let dh = if achromatic {
+            0.
+        }else if (h2p - h1p).abs() <= 180. {
+            h2p - h1p
+        }else if h2p - h1p > 180. {
+            h2p - h1p - 360.
+        }else{
+            h2p - h1p + 360.
+        };
+        let dh = 2. * (c1p * c2p).sqrt() * (dh.to_radians() / 2.).sin();
+
+        let l_bar = (l1 + l2) / 2.;
+        let c_bar = (c1p + c2p) / 2.;
+        let h_bar = if achromatic {
+            h1p + h2p
+        }else if (h1p - h2p).abs() <= 180. {
+            (h1p + h2p) / 2.
+        }else if h1p + h2p < 360. {
+            (h1p + h2p + 360.) / 2.
+        }else{
+            (h1p + h2p - 360.) / 2.
+        };
+        let cos = |deg: f64| deg.to_radians().cos();
+        let t = 1. - 0.17 * cos(h_bar - 30.) + 0.24 * cos(2. * h_bar)
+            + 0.32 * cos(3. * h_bar + 6.) - 0.20 * cos(4. * h_bar - 63.);
+        let d_theta = 30. * (-((h_bar - 275.) / 25.).powi(2)).exp();
+        let rc = 2. * (c_bar.powi(7) / (c_bar.powi(7) + pow25_7)).sqrt();
+        let sl = 1. + 0.015 * (l_bar - 50.).powi(2) / (20. + (l_bar - 50.).powi(2)).sqrt();
+        let sc = 1. + 0.045 * c_bar;
+        let sh = 1. + 0.015 * c_bar * t;
+        let rt = -(2. * d_theta).to_radians().sin() * rc;
+
+        let (dl, dc, dh) = (dl / sl, dc / sc, dh / sh);
+        cast((dl * dl + dc * dc + dh * dh + rt * dc * dh).sqrt()).unwrap()
+    }
+}
+
+#[derive(Serialize)]
+struct LabRef<'a, T: 'a> {
+    l: &'a T,
+    a: &'a T,
+    b: &'a T,
+    white_point: &'static str,
+}
+
+#[derive(Deserialize)]
+struct LabOwned<T> {
+    l: T,
+    a: T,
+    b: T,
+    white_point: String,
+}
+
+// Serializes the white point by name so data can't be loaded with the wrong one
+impl<T: Serialize, Wp: WhitePoint> Serialize for Lab<T, Wp> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LabRef {
+            l: &self.l,
+            a: &self.a,
+            b: &self.b,
+            white_point: Wp::name(),
+        }.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, Wp: WhitePoint> Deserialize<'de> for Lab<T, Wp> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let lab = LabOwned::deserialize(deserializer)?;
+        if lab.white_point != Wp::name() {
+            return Err(de::Error::custom(format!(
+                "expected white point {} but found {}", Wp::name(), lab.white_point
+            )));
+        }
+        Ok(Lab::new(lab.l, lab.a, lab.b))
+    }
+}
+
+impl<T: Float, Wp: WhitePoint + Clone> Lab<T, Wp>{
+    /// Linear interpolation `self * (1 - t) + other * t` with `t` clamped to
+    /// `[0, 1]`. Perceptually even and, unlike RGB, doesn't go through gray
+    /// as long as both colors have a similar hue.
+    pub fn mix(&self, other: &Lab<T, Wp>, t: T) -> Lab<T, Wp> {
+        let t = t.max(T::zero()).min(T::one());
+        let s = T::one() - t;
+        Lab {
+            l: self.l * s + other.l * t,
+            a: self.a * s + other.a * t,
+            b: self.b * s + other.b * t,
+            white_point: self.white_point.clone(),
+        }
+    }
+
+    /// Interpolates a* and b* by `t` with the lightness pinned to `l`, for
+    /// palettes and colormaps of constant lightness.
+    pub fn lerp_iso_lightness(&self, other: &Lab<T, Wp>, t: T, l: T) -> Lab<T, Wp> {
+        Lab {
+            l,
+            a: self.a + (other.a - self.a) * t,
+            b: self.b + (other.b - self.b) * t,
+            white_point: self.white_point.clone(),
+        }
+    }
+}
+
+// CIEDE2000 difference of each pair of pixels
+fn delta_e_pixels<'a>(a: &'a [Rgb<u8, Srgb>], b: &'a [Rgb<u8, Srgb>]) -> Result<impl Iterator<Item = f32> + 'a, ColorError> {
+    if a.len() != b.len() {
+        return Err(ColorError::LengthMismatch);
+    }
+    Ok(a.iter().zip(b).map(|(a, b)| {
+        let a: Lab<f32, D65> = a.to_xyz::<f32>().to_lab();
+        let b: Lab<f32, D65> = b.to_xyz::<f32>().to_lab();
+        a.delta_e_2000(&b)
+    }))
+}
+
+/// Mean CIEDE2000 difference between two images of the same size, 0 for
+/// empty images.
+pub fn mean_delta_e(a: &[Rgb<u8, Srgb>], b: &[Rgb<u8, Srgb>]) -> Result<f32, ColorError> {
+    let sum: f64 = delta_e_pixels(a, b)?.map(|d| d as f64).sum();
+    Ok(if a.is_empty() { 0. } else { (sum / a.len() as f64) as f32 })
+}
+
+/// Largest CIEDE2000 difference between two images of the same size, 0 for
+/// empty images.
+pub fn max_delta_e(a: &[Rgb<u8, Srgb>], b: &[Rgb<u8, Srgb>]) -> Result<f32, ColorError> {
+    Ok(delta_e_pixels(a, b)?.fold(0., f32::max))
+}
+
+/// Orders a palette into a smooth sequence, starting from the darkest color
+/// and repeatedly appending the nearest remaining one in Lab. A greedy chain
+/// so the total distance is short but not necessarily the shortest.
+pub fn sort_palette_perceptual(colors: &mut [Rgb<u8, Srgb>]) {
+    let mut labs: Vec<(Rgb<u8, Srgb>, Lab<f32, D65>)> = colors.iter()
+        .map(|c| (*c, c.to_xyz::<f32>().to_lab()))
+        .collect();
+    let darkest = labs.iter().enumerate()
+        .min_by(|(_, a), (_, b)| a.1.l.total_cmp(&b.1.l))
+        .map(|(i, _)| i);
+    let Some(darkest) = darkest else { return };
+    labs.swap(0, darkest);
+    for i in 1..labs.len() {
+        let last = labs[i - 1].1;
+        let nearest = (i..labs.len())
+            .min_by(|a, b| last.delta_e_76(&labs[*a].1).total_cmp(&last.delta_e_76(&labs[*b].1)))
+            .unwrap();
+        labs.swap(i, nearest);
+    }
+    for (c, (sorted, _)) in colors.iter_mut().zip(labs) {
+        *c = sorted;
+    }
+}
+
+/// Converts a slice of sRGB colors into Lab, writing into `dst`.
+pub fn srgb_slice_to_lab_into(src: &[Rgb<u8, Srgb>], dst: &mut [Lab<f32, D65>]) {
+    assert_eq!(src.len(), dst.len(), "The destination must have the same length as the source");
+    for (c, lab) in src.iter().zip(dst.iter_mut()) {
+        *lab = c.to_xyz::<f32>().to_lab();
+    }
+}
+
+/// Converts a slice of sRGB colors into Lab.
+pub fn srgb_slice_to_lab(src: &[Rgb<u8, Srgb>]) -> Vec<Lab<f32, D65>> {
+    let mut dst = vec![Lab::new(0., 0., 0.); src.len()];
+    srgb_slice_to_lab_into(src, &mut dst);
+    dst
+}
+
+/// Converts a slice of Lab colors into sRGB, clipping out of gamut colors,
+/// writing into `dst`.
+pub fn lab_slice_to_srgb_into(src: &[Lab<f32, D65>], dst: &mut [Rgb<u8, Srgb>]) {
+    assert_eq!(src.len(), dst.len(), "The destination must have the same length as the source");
+    for (lab, c) in src.iter().zip(dst.iter_mut()) {
+        *c = lab.to_xyz::<f32>().to_rgb();
+    }
+}
+
+/// Converts a slice of Lab colors into sRGB, clipping out of gamut colors.
+pub fn lab_slice_to_srgb(src: &[Lab<f32, D65>]) -> Vec<Rgb<u8, Srgb>> {
+    let mut dst = vec![Rgb::new(0, 0, 0); src.len()];
+    lab_slice_to_srgb_into(src, &mut dst);
+    dst
 }
 
 pub trait ToLab {
@@ -85,16 +317,281 @@ impl<T: Channel + Float + NumCast, Wp: WhitePoint> ToXyz for Lab<T, Wp> {
     }
 }
 
+impl<T: Channel + Float, Wp: WhitePoint + Copy> Color<T> for Lab<T, Wp> {
+    /// Clamps the components of the color to the range `(lo,hi)`.
+    #[inline]
+    fn clamp_s(self, lo: T, hi: T) -> Lab<T, Wp> {
+        Lab { l: Channel::clamp(self.l, lo, hi), a: Channel::clamp(self.a, lo, hi), b: Channel::clamp(self.b, lo, hi), ..self }
+    }
+
+    /// Clamps the components of the color component-wise between `lo` and `hi`.
+    #[inline]
+    fn clamp_c(self, lo: Lab<T, Wp>, hi: Lab<T, Wp>) -> Lab<T, Wp> {
+        Lab { l: Channel::clamp(self.l, lo.l, hi.l), a: Channel::clamp(self.a, lo.a, hi.a), b: Channel::clamp(self.b, lo.b, hi.b), ..self }
+    }
+
+    /// Inverts the lightness and the opponent axes.
+    #[inline]
+    fn inverse(self) -> Lab<T, Wp> {
+        Lab { l: cast::<_, T>(100).unwrap() - self.l, a: -self.a, b: -self.b, ..self }
+    }
+
+    #[inline]
+    fn mix(self, other: Lab<T, Wp>, value: T) -> Lab<T, Wp> {
+        Lab::mix(&self, &other, value)
+    }
+}
+
 impl<T: Channel + Float + NumCast, Wp: WhitePoint> Add for Lab<T,Wp>{
     type Output = Lab<T, Wp>;
     fn add(self, other: Lab<T, Wp>) -> Lab<T, Wp> {
-        Lab::new(self.l + other.l, self.a + other.a, self.b + other.b)
+        Lab {
+            l: self.l + other.l,
+            a: self.a + other.a,
+            b: self.b + other.b,
+            white_point: self.white_point,
+        }
     }
 }
 
 impl<T: Channel + Float + NumCast, Wp: WhitePoint> Mul<T> for Lab<T,Wp>{
     type Output = Lab<T, Wp>;
     fn mul(self, other: T) -> Lab<T, Wp> {
-        Lab::new(self.l * other, self.a * other, self.b * other)
+        Lab {
+            l: self.l * other,
+            a: self.a * other,
+            b: self.b * other,
+            white_point: self.white_point,
+        }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+    use super::{Lab, ToLab, mean_delta_e, max_delta_e, sort_palette_perceptual};
+    use super::{srgb_slice_to_lab, srgb_slice_to_lab_into, lab_slice_to_srgb, lab_slice_to_srgb_into};
+    use xyz::ToXyz;
+    use error::ColorError;
+    use rgb::{Rgb, ToRgb};
+    use color_space::{D50, D65, WhitePoint};
+    use xyz::Xyz;
+    use channel::Channel;
+    use num_traits::Float;
+
+    #[derive(Default, Clone, Copy, Debug, PartialEq)]
+    struct Measured(u32);
+
+    impl WhitePoint for Measured {
+        fn xyz<T: Channel + Float>() -> Xyz<T, D50> {
+            D65::xyz()
+        }
+
+        fn name() -> &'static str {
+            "Measured"
+        }
+    }
+
+    #[test]
+    fn test_color_impl() {
+        use Color;
+        let lab = Lab::<f32, D65>::new(60., -20., 30.);
+        let clamped = lab.clamp_s(-10., 10.);
+        assert_eq!((clamped.l, clamped.a, clamped.b), (10., -10., 10.));
+        let clamped = lab.clamp_c(Lab::new(0., 0., 0.), Lab::new(50., 10., 10.));
+        assert_eq!((clamped.l, clamped.a, clamped.b), (50., 0., 10.));
+        let inverse = lab.inverse();
+        assert_eq!((inverse.l, inverse.a, inverse.b), (40., 20., -30.));
+        let mixed = Color::mix(lab, inverse, 0.5);
+        assert_eq!((mixed.l, mixed.a, mixed.b), (50., 0., 0.));
+    }
+
+    #[test]
+    fn test_lab_slice_conversions() {
+        let srgb = [Rgb::new(0u8, 0, 0), Rgb::new(0x40, 0x80, 0xC0), Rgb::new(0xFF, 0x12, 0x34)];
+        let lab = srgb_slice_to_lab(&srgb);
+        let mut lab_into = [Lab::new(0., 0., 0.); 3];
+        srgb_slice_to_lab_into(&srgb, &mut lab_into);
+        for (a, b) in lab.iter().zip(lab_into.iter()) {
+            assert!(a.l == b.l && a.a == b.a && a.b == b.b, "{:?} {:?}", a, b);
+        }
+
+        let back = lab_slice_to_srgb(&lab);
+        let mut back_into = [Rgb::new(0, 0, 0); 3];
+        lab_slice_to_srgb_into(&lab, &mut back_into);
+        assert_eq!(back, back_into);
+        for (a, b) in back.iter().zip(srgb.iter()) {
+            assert!(a.within(b, 1), "{:?} {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_delta_e_2000() {
+        // Sharma's CIEDE2000 test data, the pairs around 7.1792 / 7.2195 and
+        // 4.8045 / 4.7461 straddle the mean hue discontinuity
+        let pairs = [
+            ((50., 2.6772, -79.7751), (50., 0., -82.7485), 2.0425),
+            ((50., 3.1571, -77.2803), (50., 0., -82.7485), 2.8615),
+            ((50., 2.8361, -74.0200), (50., 0., -82.7485), 3.4412),
+            ((50., -1.3802, -84.2814), (50., 0., -82.7485), 1.0000),
+            ((50., -1.1848, -84.8006), (50., 0., -82.7485), 1.0000),
+            ((50., -0.9009, -85.5211), (50., 0., -82.7485), 1.0000),
+            ((50., 0., 0.), (50., -1., 2.), 2.3669),
+            ((50., -1., 2.), (50., 0., 0.), 2.3669),
+            ((50., 2.49, -0.001), (50., -2.49, 0.0009), 7.1792),
+            ((50., 2.49, -0.001), (50., -2.49, 0.0010), 7.1792),
+            ((50., 2.49, -0.001), (50., -2.49, 0.0011), 7.2195),
+            ((50., 2.49, -0.001), (50., -2.49, 0.0012), 7.2195),
+            ((50., -0.001, 2.49), (50., 0.0009, -2.49), 4.8045),
+            ((50., -0.001, 2.49), (50., 0.0010, -2.49), 4.8045),
+            ((50., -0.001, 2.49), (50., 0.0011, -2.49), 4.7461),
+            ((50., 2.5, 0.), (50., 0., -2.5), 4.3065),
+            ((50., 2.5, 0.), (73., 25., -18.), 27.1492),
+            ((50., 2.5, 0.), (61., -5., 29.), 22.8977),
+            ((50., 2.5, 0.), (56., -27., -3.), 31.9030),
+            ((50., 2.5, 0.), (58., 24., 15.), 19.4535),
+            ((50., 2.5, 0.), (50., 3.1736, 0.5854), 1.0000),
+            ((50., 2.5, 0.), (50., 3.2972, 0.), 1.0000),
+            ((50., 2.5, 0.), (50., 1.8634, 0.5757), 1.0000),
+            ((50., 2.5, 0.), (50., 3.2592, 0.3350), 1.0000),
+            ((60.2574, -34.0099, 36.2677), (60.4626, -34.1751, 39.4387), 1.2644),
+            ((63.0109, -31.0961, -5.8663), (62.8187, -29.7946, -4.0864), 1.2630),
+            ((61.2901, 3.7196, -5.3901), (61.4292, 2.2480, -4.9620), 1.8731),
+            ((35.0831, -44.1164, 3.7933), (35.0232, -40.0716, 1.5901), 1.8645),
+            ((22.7233, 20.0904, -46.6940), (23.0331, 14.9730, -42.5619), 2.0373),
+            ((36.4612, 47.8580, 18.3852), (36.2715, 50.5065, 21.2231), 1.4146),
+            ((90.8027, -2.0831, 1.4410), (91.1528, -1.6435, 0.0447), 1.4441),
+            ((90.9257, -0.5406, -0.9208), (88.6381, -0.8985, -0.7239), 1.5381),
+            ((6.7747, -0.2908, -2.4247), (5.8714, -0.0985, -2.2286), 0.6377),
+            ((2.0776, 0.0795, -1.1350), (0.9033, -0.0636, -0.5514), 0.9082),
+        ];
+        for &((l1, a1, b1), (l2, a2, b2), expected) in pairs.iter() {
+            let lab1 = Lab::<f64, D65>::new(l1, a1, b1);
+            let lab2 = Lab::<f64, D65>::new(l2, a2, b2);
+            assert!((lab1.delta_e_2000(&lab2) - expected).abs() < 1e-4, "{:?} {:?}", lab1, lab2);
+            assert!((lab2.delta_e_2000(&lab1) - expected).abs() < 1e-4, "{:?} {:?}", lab1, lab2);
+        }
+    }
+
+    #[test]
+    fn test_srgb_lab_round_trip_grid() {
+        // 0 to 255 in steps of 3, 255 included
+        let levels: Vec<u8> = (0..=255u8).step_by(3).collect();
+        for &r in &levels {
+            for &g in &levels {
+                for &b in &levels {
+                    let c = Rgb::<u8>::new(r, g, b);
+                    let lab: Lab<f32, D65> = c.to_xyz::<f32>().to_lab();
+                    let back: Rgb<u8> = lab.to_xyz::<f32>().to_rgb();
+                    assert!(back.within(&c, 1), "{:?} {:?} {:?}", c, lab, back);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_delta_e_metrics() {
+        let lab1 = Lab::<f64, D65>::new(50., 10., 10.);
+        let lab2 = Lab::<f64, D65>::new(50.5, 10.3, 10.2);
+        let de76 = lab1.delta_e_76(&lab2);
+        assert!((de76 - 0.616).abs() < 1e-3, "{}", de76);
+        assert!((lab1.delta_e_94(&lab2) - de76).abs() < 0.1, "{}", lab1.delta_e_94(&lab2));
+        assert!((lab1.delta_e_2000(&lab2) - de76).abs() < 0.2, "{}", lab1.delta_e_2000(&lab2));
+
+        // CIE94 weights chroma differences down for saturated colors
+        let lab1 = Lab::<f64, D65>::new(50., 80., 0.);
+        let lab2 = Lab::<f64, D65>::new(50., 40., 0.);
+        assert_eq!(lab1.delta_e_76(&lab2), 40.);
+        assert!((lab1.delta_e_94(&lab2) - 40. / 4.6).abs() < 1e-9, "{}", lab1.delta_e_94(&lab2));
+        assert_eq!(lab1.delta_e_76(&lab1), 0.);
+        assert_eq!(lab1.delta_e_94(&lab1), 0.);
+    }
+
+    #[test]
+    fn test_mix() {
+        let start = Lab::<f32, D65>::new(30., 60., -20.);
+        let end = Lab::<f32, D65>::new(85., -40., 70.);
+        let at = |t: f32| { let c = start.mix(&end, t); (c.l, c.a, c.b) };
+        assert_eq!(at(0.), (start.l, start.a, start.b));
+        assert_eq!(at(1.), (end.l, end.a, end.b));
+        assert_eq!(at(-1.), at(0.));
+        assert_eq!(at(2.), at(1.));
+        let mid = start.mix(&end, 0.5);
+        assert!(mid.l > start.l && mid.l < end.l, "{:?}", mid);
+        assert_eq!((mid.l, mid.a, mid.b), (57.5, 10., 25.));
+    }
+
+    #[test]
+    fn test_lerp_iso_lightness() {
+        let start = Lab::<f32, D65>::new(30., 60., -20.);
+        let end = Lab::<f32, D65>::new(85., -40., 70.);
+        for i in 0..=10 {
+            let t = i as f32 / 10.;
+            let c = start.lerp_iso_lightness(&end, t, 65.);
+            assert_eq!(c.l, 65.);
+            assert!((c.a - (60. - 100. * t)).abs() < 1e-4, "{:?}", c);
+            assert!((c.b - (-20. + 90. * t)).abs() < 1e-4, "{:?}", c);
+        }
+    }
+
+    #[test]
+    fn test_mean_max_delta_e() {
+        let image: Vec<Rgb<u8>> = (0..16u8).map(|i| rgb!(i * 16, 0x80, 0xFF - i * 16)).collect();
+        assert_eq!(mean_delta_e(&image, &image), Ok(0.));
+        assert_eq!(max_delta_e(&image, &image), Ok(0.));
+
+        let shifted: Vec<Rgb<u8>> = image.iter().map(|c| rgb!(c.r, c.g + 8, c.b)).collect();
+        let mean = mean_delta_e(&image, &shifted).unwrap();
+        let max = max_delta_e(&image, &shifted).unwrap();
+        assert!(mean > 1., "{}", mean);
+        assert!(max >= mean);
+        assert_eq!(mean_delta_e(&image, &shifted[1..]), Err(ColorError::LengthMismatch));
+        assert_eq!(max_delta_e(&image[1..], &shifted), Err(ColorError::LengthMismatch));
+    }
+
+    #[test]
+    fn test_sort_palette_perceptual() {
+        let total = |colors: &[Rgb<u8>]| -> f32 {
+            colors.windows(2).map(|w| {
+                let a: Lab<f32, D65> = w[0].to_xyz::<f32>().to_lab();
+                let b: Lab<f32, D65> = w[1].to_xyz::<f32>().to_lab();
+                a.delta_e_76(&b)
+            }).sum()
+        };
+        let mut palette = vec![
+            rgb!(0xFFu8, 0xFF, 0xFF), rgb!(0x20, 0x40, 0xC0), rgb!(0xF0, 0x40, 0x30),
+            rgb!(0x10, 0x10, 0x10), rgb!(0xE0, 0xE0, 0xD0), rgb!(0x30, 0x60, 0xE0),
+            rgb!(0xC0, 0x20, 0x20), rgb!(0x80, 0x80, 0x80), rgb!(0x40, 0xA0, 0x40),
+            rgb!(0x30, 0x80, 0x30),
+        ];
+        let unsorted = total(&palette);
+        let mut sorted = palette.clone();
+        sort_palette_perceptual(&mut sorted);
+        assert!(total(&sorted) < unsorted, "{} {}", total(&sorted), unsorted);
+        assert_eq!(sorted[0], rgb!(0x10, 0x10, 0x10));
+
+        // same colors, just reordered
+        palette.sort_by_key(|c| (c.r, c.g, c.b));
+        sorted.sort_by_key(|c| (c.r, c.g, c.b));
+        assert_eq!(palette, sorted);
+
+        sort_palette_perceptual(&mut []);
+    }
+
+    #[test]
+    fn test_arithmetic_preserves_white_point() {
+        let lab = Lab { l: 50f32, a: 10., b: -20., white_point: Measured(7) };
+        let other = Lab { l: 10f32, a: 1., b: 2., white_point: Measured(7) };
+        assert_eq!((lab + other).white_point, Measured(7));
+        assert_eq!((lab * 0.5).white_point, Measured(7));
+    }
+
+    #[test]
+    fn test_serialize_white_point() {
+        let lab = Lab::<f32, D50>::new(50., 10., -20.);
+        let json = serde_json::to_string(&lab).unwrap();
+        assert_eq!(json, r#"{"l":50.0,"a":10.0,"b":-20.0,"white_point":"D50"}"#);
+        let back: Lab<f32, D50> = serde_json::from_str(&json).unwrap();
+        assert_eq!((back.l, back.a, back.b), (50., 10., -20.));
+        assert!(serde_json::from_str::<Lab<f32, D65>>(&json).is_err());
+    }
+}
@@ -0,0 +1,155 @@
+use channel::Channel;
+use color_space::WhitePoint;
+use lab::{Lab, ToLab};
+use hue::normalize_radians;
+use num_traits::{Float, cast};
+use std::f64::consts::{PI, TAU};
+
+/// Cylindrical form of `Lab`: lightness, chroma and hue in radians.
+///
+/// The white point is carried through conversions so a D50 `Lch` can only
+/// go back to a D50 `Lab`:
+///
+/// ```compile_fail
+/// use color::{Lab, Lch, ToLab};
+/// use color::color_space::{D50, D65};
+/// let lch = Lch::<f32, D50>::new(50., 20., 1.);
+/// let lab: Lab<f32, D65> = lch.to_lab();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Lch<T, Wp>{
+    pub l: T,
+    pub c: T,
+    pub h: T,
+    pub white_point: Wp,
+}
+
+impl<T, Wp: WhitePoint> Lch<T, Wp>{
+    pub fn new(l: T, c: T, h: T) -> Lch<T, Wp>{
+        Lch { l, c, h, white_point: Wp::default() }
+    }
+}
+
+/// Which way around the hue circle to interpolate, as in CSS Color 4.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum HueDirection {
+    /// The arc of at most 180 degrees.
+    #[default]
+    Shorter,
+    /// The arc of at least 180 degrees.
+    Longer,
+    /// Counterclockwise, with increasing hue angles.
+    Increasing,
+    /// Clockwise, with decreasing hue angles.
+    Decreasing,
+}
+
+impl<T: Float, Wp: WhitePoint + Clone> Lch<T, Wp> {
+    /// Interpolates lightness and chroma linearly and the hue along the arc
+    /// picked by `direction`, `t` is clamped to `[0, 1]`.
+    pub fn mix(&self, other: &Lch<T, Wp>, t: T, direction: HueDirection) -> Lch<T, Wp> {
+        let t = t.max(T::zero()).min(T::one());
+        let tau: T = cast(TAU).unwrap();
+        let pi: T = cast(PI).unwrap();
+        let mut h1 = normalize_radians(self.h);
+        let mut h2 = normalize_radians(other.h);
+        let dh = h2 - h1;
+        match direction {
+            HueDirection::Shorter => if dh > pi {
+                h1 = h1 + tau;
+            }else if dh < -pi {
+                h2 = h2 + tau;
+            },
+            HueDirection::Longer => if dh > T::zero() && dh < pi {
+                h1 = h1 + tau;
+            }else if dh > -pi && dh <= T::zero() {
+                h2 = h2 + tau;
+            },
+            HueDirection::Increasing => if h2 < h1 {
+                h2 = h2 + tau;
+            },
+            HueDirection::Decreasing => if h1 < h2 {
+                h1 = h1 + tau;
+            },
+        }
+        Lch {
+            l: self.l + (other.l - self.l) * t,
+            c: self.c + (other.c - self.c) * t,
+            h: normalize_radians(h1 + (h2 - h1) * t),
+            white_point: self.white_point.clone(),
+        }
+    }
+}
+
+pub trait ToLch {
+    type WhitePoint: WhitePoint;
+    fn to_lch<T: Channel + Float>(&self) -> Lch<T, Self::WhitePoint>;
+}
+
+impl<T: Channel + Float, Wp: WhitePoint + Clone> ToLch for Lab<T, Wp> {
+    type WhitePoint = Wp;
+    fn to_lch<U: Channel + Float>(&self) -> Lch<U, Wp> {
+        Lch {
+            l: self.l.to_channel(),
+            c: self.chromacity().to_channel(),
+            h: self.hue().to_channel(),
+            white_point: self.white_point.clone(),
+        }
+    }
+}
+
+impl<T: Channel + Float, Wp: WhitePoint + Clone> ToLab for Lch<T, Wp> {
+    type WhitePoint = Wp;
+    fn to_lab<U: Channel>(&self) -> Lab<U, Wp> {
+        Lab {
+            l: self.l.to_channel(),
+            a: (self.c * self.h.cos()).to_channel(),
+            b: (self.c * self.h.sin()).to_channel(),
+            white_point: self.white_point.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lab::{Lab, ToLab};
+    use color_space::{D50, D65};
+    use rgb::Rgb;
+    use xyz::ToXyz;
+    use super::{Lch, ToLch, HueDirection};
+
+    #[test]
+    fn test_mix_hue_direction() {
+        let lch = |c: Rgb<u8>| -> Lch<f64, D65> { c.to_xyz::<f64>().to_lab::<f64>().to_lch() };
+        let red = lch(Rgb::new(0xFF, 0, 0));
+        let green = lch(Rgb::new(0, 0xFF, 0));
+        let yellow = lch(Rgb::new(0xFF, 0xFF, 0)).h.to_degrees();
+        let blue = lch(Rgb::new(0, 0, 0xFF)).h.to_degrees();
+        let mid = |direction| red.mix(&green, 0.5, direction).h.to_degrees();
+        // red is around 40 degrees, green 136, yellow 102 and blue 306
+        assert!((mid(HueDirection::Shorter) - yellow).abs() < 20., "{} {}", mid(HueDirection::Shorter), yellow);
+        assert!((mid(HueDirection::Increasing) - yellow).abs() < 20.);
+        assert!((mid(HueDirection::Longer) - blue).abs() < 40., "{} {}", mid(HueDirection::Longer), blue);
+        assert!((mid(HueDirection::Decreasing) - blue).abs() < 40.);
+        assert!((mid(HueDirection::Longer) - mid(HueDirection::Shorter) - 180.).abs() < 1e-9);
+
+        let start = red.mix(&green, 0., HueDirection::Longer);
+        let end = red.mix(&green, 1., HueDirection::Longer);
+        assert!((start.h - red.h).abs() < 1e-9 && (start.l - red.l).abs() < 1e-9);
+        assert!((end.h - green.h).abs() < 1e-9 && (end.c - green.c).abs() < 1e-9);
+        let half = red.mix(&green, 0.5, HueDirection::Shorter);
+        assert!((half.l - (red.l + green.l) / 2.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_lab_lch_round_trip() {
+        let lab = Lab::<f64, D50>::new(52., -31.5, 18.25);
+        let lch: Lch<f64, D50> = lab.to_lch();
+        assert!((lch.c - lab.chromacity()).abs() < 1e-12);
+        assert!((lch.h - lab.hue()).abs() < 1e-12);
+        let back: Lab<f64, D50> = lch.to_lab();
+        assert!((back.l - lab.l).abs() < 1e-12);
+        assert!((back.a - lab.a).abs() < 1e-12);
+        assert!((back.b - lab.b).abs() < 1e-12);
+    }
+}
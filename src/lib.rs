@@ -19,14 +19,16 @@ extern crate half;
 #[macro_use] extern crate serde_derive;
 #[cfg(feature="kmeans")]
 extern crate kmeans_colors;
-#[cfg(feature="kmeans")]
+#[cfg(feature="rand")]
 extern crate rand;
 
 pub use alpha::AlphaColor;
-pub use alpha::{Rgba, Hsva, YCbCra, ToRgba, LumaA};
-pub use channel::{Channel, FloatChannel};
+pub use alpha::{Rgba, Hsva, YCbCra, ToRgba, LumaA, BlendMode, composite_stack};
+pub use channel::{Channel, FloatChannel, RoundingMode};
 pub use hsv::{Hsv, ToHsv};
-pub use rgb::{Rgb, Rg, ToRgb, consts};
+pub use hsl::{Hsl, ToHsl};
+pub use rgb::{Rgb, Rg, ToRgb, Temperature, ColorCategory, consts, deinterleave, interleave, apply_exposure, apply_gamma, apca_contrast, contrast_ratio};
+pub use rgb::{srgb_slice_to_linear, srgb_slice_to_linear_into, linear_slice_to_srgb, linear_slice_to_srgb_into};
 // pub use srgb::{Srgb, ToSrgb};
 pub use ycbcr::YCbCr;
 pub use angle::Deg;
@@ -34,21 +36,50 @@ pub use luma::{Luma, ToLuma};
 pub use xyz::{Xyz, ToXyz};
 // pub use color_space::{MatrixColorSpace, WhitePoint, TransferFunction, Srgb};
 pub use yxy::{Yxy, ToYxy};
-pub use lab::{Lab, ToLab};
+pub use lab::{Lab, ToLab, mean_delta_e, max_delta_e, sort_palette_perceptual};
+pub use lab::{srgb_slice_to_lab, srgb_slice_to_lab_into, lab_slice_to_srgb, lab_slice_to_srgb_into};
+pub use lch::{Lch, ToLch, HueDirection};
+pub use luv::{Luv, ToLuv};
+pub use oklab::{Oklab, ToOklab};
+pub use oklch::{Oklch, ToOklch};
+pub use cmyk::{Cmyk, ToCmyk};
+pub use gamut::{GamutMapMethod, process_in_lab, equalize_lightness, equalize_lightness_clipped, retheme_palette};
+pub use gradient::{Gradient, GradientStops, BakedGradient};
+pub use error::{ColorError, ParseError};
+pub use lut::Lut3D;
+pub use convert::{ColorSpace, convert_between};
 
 #[macro_use] mod rgb;
 #[macro_use] mod alpha;
 mod channel;
 mod hsv;
+mod hsl;
+mod hue;
 // pub mod srgb;
 mod ycbcr;
 mod luma;
 mod xyz;
 mod yxy;
 mod lab;
+mod lch;
+mod luv;
+mod oklab;
+mod oklch;
+mod cmyk;
+mod gamut;
+mod gradient;
+mod error;
+mod lut;
+mod convert;
 pub mod color_space;
+mod adobe_rgb;
+mod p3;
+mod rec2020;
+pub mod serde_hex;
 #[cfg(feature="kmeans")]
 pub mod kmeans;
+#[cfg(test)]
+mod proptests;
 
 pub trait Color<T>: Copy {
     fn clamp_s(self, lo: T, hi: T) -> Self;
@@ -0,0 +1,111 @@
+//! 3D lookup tables for color grading
+
+use rgb::Rgb;
+use color_space::Srgb;
+
+/// An `N×N×N` grid of sRGB colors sampled with trilinear interpolation.
+///
+/// The LUT works on sRGB encoded values in `[0, 1]`, as exported by most
+/// grading tools. Entries are stored in `.cube` order, red changing fastest.
+#[derive(Clone, Debug)]
+pub struct Lut3D {
+    size: usize,
+    table: Vec<Rgb<f32, Srgb>>,
+}
+
+impl Lut3D {
+    /// Builds a LUT from `size³` entries in `.cube` order.
+    pub fn new(size: usize, table: Vec<Rgb<f32, Srgb>>) -> Lut3D {
+        assert!(size >= 2, "A 3D LUT needs at least 2 samples per axis");
+        assert_eq!(table.len(), size * size * size, "A 3D LUT of size {} needs {} entries", size, size * size * size);
+        Lut3D { size, table }
+    }
+
+    /// Builds a LUT sampling `f` at each grid point.
+    pub fn from_fn<F: FnMut(Rgb<f32, Srgb>) -> Rgb<f32, Srgb>>(size: usize, mut f: F) -> Lut3D {
+        assert!(size >= 2, "A 3D LUT needs at least 2 samples per axis");
+        let last = (size - 1) as f32;
+        let mut table = Vec::with_capacity(size * size * size);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    table.push(f(Rgb::new(r as f32 / last, g as f32 / last, b as f32 / last)));
+                }
+            }
+        }
+        Lut3D::new(size, table)
+    }
+
+    /// LUT that leaves every color unchanged.
+    pub fn identity(size: usize) -> Lut3D {
+        Lut3D::from_fn(size, |c| c)
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn table(&self) -> &[Rgb<f32, Srgb>] {
+        &self.table
+    }
+
+    #[inline]
+    fn at(&self, r: usize, g: usize, b: usize) -> Rgb<f32, Srgb> {
+        self.table[r + self.size * (g + self.size * b)]
+    }
+
+    /// Transforms `color` interpolating trilinearly between the closest 8
+    /// grid points. Channels are clamped to `[0, 1]` first.
+    pub fn apply(&self, color: Rgb<f32, Srgb>) -> Rgb<f32, Srgb> {
+        let last = (self.size - 1) as f32;
+        let split = |c: f32| {
+            let x = c.clamp(0., 1.) * last;
+            let i = (x.floor() as usize).min(self.size - 2);
+            (i, x - i as f32)
+        };
+        let (r, fr) = split(color.r);
+        let (g, fg) = split(color.g);
+        let (b, fb) = split(color.b);
+
+        let lerp = |c0: Rgb<f32, Srgb>, c1: Rgb<f32, Srgb>, t: f32| Rgb::new(
+            c0.r + (c1.r - c0.r) * t,
+            c0.g + (c1.g - c0.g) * t,
+            c0.b + (c1.b - c0.b) * t,
+        );
+        let c00 = lerp(self.at(r, g, b), self.at(r + 1, g, b), fr);
+        let c10 = lerp(self.at(r, g + 1, b), self.at(r + 1, g + 1, b), fr);
+        let c01 = lerp(self.at(r, g, b + 1), self.at(r + 1, g, b + 1), fr);
+        let c11 = lerp(self.at(r, g + 1, b + 1), self.at(r + 1, g + 1, b + 1), fr);
+        lerp(lerp(c00, c10, fg), lerp(c01, c11, fg), fb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lut3D;
+
+    #[test]
+    fn test_identity() {
+        let lut = Lut3D::identity(17);
+        for c in [rgb!(0f32, 0., 0.), rgb!(1f32, 1., 1.), rgb!(0.3f32, 0.71, 0.02), rgb!(0.5f32, 0.999, 0.0625)].iter() {
+            let out = lut.apply(*c);
+            assert!((out.r - c.r).abs() < 1e-6, "{:?} {:?}", c, out);
+            assert!((out.g - c.g).abs() < 1e-6, "{:?} {:?}", c, out);
+            assert!((out.b - c.b).abs() < 1e-6, "{:?} {:?}", c, out);
+        }
+    }
+
+    #[test]
+    fn test_remap() {
+        let lut = Lut3D::from_fn(2, |c| rgb!(1. - c.b, c.r * 0.5, c.g));
+        let out = lut.apply(rgb!(0.2, 0.4, 0.6));
+        assert!((out.r - 0.4).abs() < 1e-6, "{:?}", out);
+        assert!((out.g - 0.1).abs() < 1e-6, "{:?}", out);
+        assert!((out.b - 0.4).abs() < 1e-6, "{:?}", out);
+
+        // Order of the table entries matches .cube files
+        assert_eq!(lut.table()[1], rgb!(1., 0.5, 0.));
+        assert_eq!(lut.table()[2], rgb!(1., 0., 1.));
+        assert_eq!(lut.table()[4], rgb!(0., 0., 0.));
+    }
+}
@@ -0,0 +1,138 @@
+use channel::Channel;
+use color_space::WhitePoint;
+use hue::normalize_radians;
+use xyz::{Xyz, ToXyz};
+use num_traits::{Float, cast, zero};
+
+/// CIE L\*u\*v\* relative to the white point `Wp`.
+///
+/// Better suited than `Lab` to additive light, mixing two lights falls on
+/// the straight line between them in the u'v' chromaticity plane.
+#[derive(Clone, Copy, Debug)]
+pub struct Luv<T, Wp>{
+    pub l: T,
+    pub u: T,
+    pub v: T,
+    pub white_point: Wp,
+}
+
+impl<T, Wp: WhitePoint> Luv<T, Wp>{
+    pub fn new(l: T, u: T, v: T) -> Luv<T, Wp>{
+        Luv { l, u, v, white_point: Wp::default() }
+    }
+}
+
+impl<T: Float, Wp: WhitePoint> Luv<T, Wp>{
+    /// Chroma of the cylindrical LCh(uv) form.
+    pub fn chroma(&self) -> T {
+        self.u.hypot(self.v)
+    }
+
+    /// Hue in radians of the cylindrical LCh(uv) form.
+    pub fn hue(&self) -> T {
+        normalize_radians(self.v.atan2(self.u))
+    }
+}
+
+// u'v' chromaticity, None for black where it's undefined
+fn uv_prime<T: Float>(x: T, y: T, z: T) -> Option<(T, T)> {
+    let denom = x + y * cast(15.).unwrap() + z * cast(3.).unwrap();
+    if denom == zero() {
+        None
+    }else{
+        Some((x * cast(4.).unwrap() / denom, y * cast(9.).unwrap() / denom))
+    }
+}
+
+fn white_uv_prime<T: Channel + Float, Wp: WhitePoint>() -> (T, T) {
+    let white = Wp::xyz::<T>();
+    uv_prime(white.x, white.y, white.z).unwrap()
+}
+
+pub trait ToLuv {
+    type WhitePoint: WhitePoint;
+    fn to_luv<T: Channel + Float>(&self) -> Luv<T, Self::WhitePoint>;
+}
+
+impl<T: Channel + Float, Wp: WhitePoint> ToLuv for Luv<T, Wp> {
+    type WhitePoint = Wp;
+    fn to_luv<U: Channel + Float>(&self) -> Luv<U, Wp> {
+        Luv::new(self.l.to_channel(), self.u.to_channel(), self.v.to_channel())
+    }
+}
+
+impl<T: Channel + Float, Wp: WhitePoint> ToLuv for Xyz<T, Wp> {
+    type WhitePoint = Wp;
+    fn to_luv<U: Channel + Float>(&self) -> Luv<U, Wp> {
+        let (un, vn) = white_uv_prime::<T, Wp>();
+        // Black has no chromaticity, take the white point's so u and v are 0
+        let (u, v) = uv_prime(self.x, self.y, self.z).unwrap_or((un, vn));
+        let yr = self.y / Wp::xyz().y;
+        let l = if yr > cast(216.0 / 24389.).unwrap() { // See BruceLindbloom.com
+            yr.cbrt() * cast(116.).unwrap() - cast(16.).unwrap()
+        }else{
+            yr * cast(24389.0 / 27.).unwrap()
+        };
+        let k: T = l * cast(13.).unwrap();
+        Luv::new(l.to_channel(), (k * (u - un)).to_channel(), (k * (v - vn)).to_channel())
+    }
+}
+
+impl<T: Channel + Float, Wp: WhitePoint> ToXyz for Luv<T, Wp> {
+    type WhitePoint = Wp;
+    fn to_xyz<U: Channel + Float>(&self) -> Xyz<U, Wp> {
+        if self.l <= zero() {
+            return Xyz::new(zero(), zero(), zero());
+        }
+        let (un, vn) = white_uv_prime::<T, Wp>();
+        let k: T = self.l * cast(13.).unwrap();
+        let u = self.u / k + un;
+        let v = self.v / k + vn;
+        let y = if self.l > cast(8.).unwrap() { // See BruceLindbloom.com
+            ((self.l + cast(16.).unwrap()) / cast(116.).unwrap()).powi(3)
+        }else{
+            self.l * cast(27.0 / 24389.).unwrap()
+        } * Wp::xyz().y;
+        let x = y * u * cast(9.).unwrap() / (v * cast(4.).unwrap());
+        let z = y * (cast::<_, T>(12.).unwrap() - u * cast(3.).unwrap() - v * cast(20.).unwrap()) / (v * cast(4.).unwrap());
+        Xyz::new(x.to_channel(), y.to_channel(), z.to_channel())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use color_space::{D50, D65, WhitePoint};
+    use rgb::Rgb;
+    use xyz::{Xyz, ToXyz};
+    use super::{Luv, ToLuv};
+
+    #[test]
+    fn test_reference_values() {
+        // sRGB red, see http://www.brucelindbloom.com/index.html?ColorCalculator.html
+        let luv: Luv<f64, D65> = Rgb::<u8>::new(0xFF, 0, 0).to_xyz::<f64>().to_luv();
+        assert!((luv.l - 53.24).abs() < 0.01, "{:?}", luv);
+        assert!((luv.u - 175.01).abs() < 0.05, "{:?}", luv);
+        assert!((luv.v - 37.76).abs() < 0.05, "{:?}", luv);
+    }
+
+    #[test]
+    fn test_gray_round_trip() {
+        let gray = Xyz::<f64, D50>::new(0.96422 * 0.18, 0.18, 0.82521 * 0.18);
+        let luv: Luv<f64, D50> = gray.to_luv();
+        assert!(luv.u.abs() < 1e-9 && luv.v.abs() < 1e-9, "{:?}", luv);
+        let back: Xyz<f64, D50> = luv.to_xyz();
+        assert!((back.x - gray.x).abs() < 1e-9 && (back.y - gray.y).abs() < 1e-9 && (back.z - gray.z).abs() < 1e-9, "{:?}", back);
+
+        let white = D65::xyz::<f64>();
+        let luv: Luv<f64, D65> = Xyz::<f64, D65>::new(white.x, white.y, white.z).to_luv();
+        assert!((luv.l - 100.).abs() < 1e-9 && luv.chroma() < 1e-9, "{:?}", luv);
+    }
+
+    #[test]
+    fn test_black() {
+        let luv: Luv<f64, D65> = Xyz::<f64, D65>::new(0., 0., 0.).to_luv();
+        assert_eq!((luv.l, luv.u, luv.v), (0., 0., 0.));
+        let xyz: Xyz<f64, D65> = luv.to_xyz();
+        assert_eq!((xyz.x, xyz.y, xyz.z), (0., 0., 0.));
+    }
+}
@@ -0,0 +1,135 @@
+use channel::Channel;
+use color_space::{D65, Mat3, Vec3};
+use xyz::{Xyz, ToXyz};
+use num_traits::{Float, cast};
+
+// See https://bottosson.github.io/posts/oklab/
+const XYZ_TO_LMS: [f64; 9] = [
+    0.8189330101, 0.3618667424, -0.1288597137,
+    0.0329845436, 0.9293118715,  0.0361456387,
+    0.0482003018, 0.2643662691,  0.6338517070,
+];
+
+const LMS_TO_XYZ: [f64; 9] = [
+    1.2270138511, -0.5577999807,  0.2812561490,
+   -0.0405801784,  1.1122568696, -0.0716766787,
+   -0.0763812845, -0.4214819784,  1.5861632204,
+];
+
+const LMS_TO_OKLAB: [f64; 9] = [
+    0.2104542553,  0.7936177850, -0.0040720468,
+    1.9779984951, -2.4285922050,  0.4505937099,
+    0.0259040371,  0.7827717662, -0.8086757660,
+];
+
+const OKLAB_TO_LMS: [f64; 9] = [
+    1.0,  0.3963377774,  0.2158037573,
+    1.0, -0.1055613458, -0.0638541728,
+    1.0, -0.0894841775, -1.2914855480,
+];
+
+fn mat<T: Float>(m: [f64; 9]) -> Mat3<T> {
+    Mat3(m.map(|v| cast(v).unwrap()))
+}
+
+pub fn xyz_to_oklab<T: Channel + Float>(xyz: [T; 3]) -> [T; 3] {
+    let lms = mat(XYZ_TO_LMS) * Vec3(xyz);
+    (mat(LMS_TO_OKLAB) * Vec3([lms[0].cbrt(), lms[1].cbrt(), lms[2].cbrt()])).0
+}
+
+pub fn oklab_to_xyz<T: Channel + Float>(lab: [T; 3]) -> [T; 3] {
+    let lms = mat(OKLAB_TO_LMS) * Vec3(lab);
+    let lms = Vec3([lms[0].powi(3), lms[1].powi(3), lms[2].powi(3)]);
+    (mat(LMS_TO_XYZ) * lms).0
+}
+
+/// Oklab perceptual color space. Defined relative to D65, lightness goes
+/// from 0 to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Oklab<T = f32>{
+    pub l: T,
+    pub a: T,
+    pub b: T,
+}
+
+impl<T> Oklab<T>{
+    pub const fn new(l: T, a: T, b: T) -> Oklab<T>{
+        Oklab { l, a, b }
+    }
+}
+
+pub trait ToOklab {
+    fn to_oklab<T: Channel + Float>(&self) -> Oklab<T>;
+}
+
+impl<T: Channel + Float> ToOklab for Oklab<T> {
+    fn to_oklab<U: Channel + Float>(&self) -> Oklab<U> {
+        Oklab::new(self.l.to_channel(), self.a.to_channel(), self.b.to_channel())
+    }
+}
+
+impl<T: Channel + Float> ToOklab for Xyz<T, D65> {
+    fn to_oklab<U: Channel + Float>(&self) -> Oklab<U> {
+        let [l, a, b] = xyz_to_oklab([self.x, self.y, self.z]);
+        Oklab::new(l.to_channel(), a.to_channel(), b.to_channel())
+    }
+}
+
+impl<T: Channel + Float> ToXyz for Oklab<T> {
+    type WhitePoint = D65;
+    fn to_xyz<U: Channel + Float>(&self) -> Xyz<U, D65> {
+        let [x, y, z] = oklab_to_xyz([self.l, self.a, self.b]);
+        Xyz::new(x.to_channel(), y.to_channel(), z.to_channel())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xyz::{Xyz, ToXyz};
+    use rgb::Rgb;
+    use color_space::D65;
+    use super::{Oklab, ToOklab};
+
+    fn assert_close(lab: Oklab<f64>, expected: (f64, f64, f64), eps: f64) {
+        assert!((lab.l - expected.0).abs() < eps
+            && (lab.a - expected.1).abs() < eps
+            && (lab.b - expected.2).abs() < eps, "{:?} {:?}", lab, expected);
+    }
+
+    #[test]
+    fn test_reference_values() {
+        // Table from Björn Ottosson's Oklab post, rounded to 3 decimals
+        let table = [
+            ((0.950, 1.000, 1.089), (1.000, 0.000, 0.000)),
+            ((1.000, 0.000, 0.000), (0.450, 1.236, -0.019)),
+            ((0.000, 1.000, 0.000), (0.922, -0.671, 0.263)),
+            ((0.000, 0.000, 1.000), (0.153, -1.415, -0.449)),
+        ];
+        for &((x, y, z), expected) in table.iter() {
+            assert_close(Xyz::<f64, D65>::new(x, y, z).to_oklab(), expected, 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_srgb() {
+        let colors = [
+            (Rgb::<u8>::new(0xFF, 0xFF, 0xFF), (1., 0., 0.)),
+            (Rgb::new(0xFF, 0, 0), (0.62796, 0.22486, 0.12585)),
+            (Rgb::new(0, 0xFF, 0), (0.86644, -0.23389, 0.17950)),
+            (Rgb::new(0, 0, 0xFF), (0.45201, -0.03246, -0.31153)),
+            (Rgb::new(0x80, 0x80, 0x80), (0.59987, 0., 0.)),
+        ];
+        for &(rgb, expected) in colors.iter() {
+            assert_close(rgb.to_xyz::<f64>().to_oklab(), expected, 5e-4);
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let xyz = Xyz::<f64, D65>::new(0.3, 0.2, 0.6);
+        let back = xyz.to_oklab::<f64>().to_xyz::<f64>();
+        assert!((back.x - xyz.x).abs() < 1e-6, "{:?}", back);
+        assert!((back.y - xyz.y).abs() < 1e-6, "{:?}", back);
+        assert!((back.z - xyz.z).abs() < 1e-6, "{:?}", back);
+    }
+}
@@ -0,0 +1,87 @@
+use channel::Channel;
+use hue::normalize_radians;
+use oklab::{Oklab, ToOklab};
+use num_traits::{Float, cast, zero};
+
+/// Cylindrical form of `Oklab`: lightness, chroma and hue in radians.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Oklch<T = f32>{
+    pub l: T,
+    pub c: T,
+    pub h: T,
+}
+
+impl<T> Oklch<T>{
+    pub const fn new(l: T, c: T, h: T) -> Oklch<T>{
+        Oklch { l, c, h }
+    }
+}
+
+pub trait ToOklch {
+    fn to_oklch<T: Channel + Float>(&self) -> Oklch<T>;
+}
+
+impl<T: Channel + Float> ToOklch for Oklch<T> {
+    fn to_oklch<U: Channel + Float>(&self) -> Oklch<U> {
+        Oklch::new(self.l.to_channel(), self.c.to_channel(), self.h.to_channel())
+    }
+}
+
+/// Colors with a chroma below `1e-4`, far from noticeable, are treated as
+/// grays with a hue of 0 so conversion noise doesn't produce random hues.
+impl<T: Channel + Float> ToOklch for Oklab<T> {
+    fn to_oklch<U: Channel + Float>(&self) -> Oklch<U> {
+        let c = self.a.hypot(self.b);
+        let h = if c < cast(1e-4).unwrap() {
+            zero()
+        }else{
+            normalize_radians(self.b.atan2(self.a))
+        };
+        Oklch::new(self.l.to_channel(), c.to_channel(), h.to_channel())
+    }
+}
+
+impl<T: Channel + Float> ToOklab for Oklch<T> {
+    fn to_oklab<U: Channel + Float>(&self) -> Oklab<U> {
+        Oklab::new(
+            self.l.to_channel(),
+            (self.c * self.h.cos()).to_channel(),
+            (self.c * self.h.sin()).to_channel(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use oklab::{Oklab, ToOklab};
+    use rgb::Rgb;
+    use xyz::ToXyz;
+    use super::{Oklch, ToOklch};
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_hue_sweep_round_trip() {
+        for i in 0..36 {
+            let h = i as f64 * PI / 18.;
+            let lch = Oklch::new(0.7, 0.12, h);
+            let lab: Oklab<f64> = lch.to_oklab();
+            let back: Oklch<f64> = lab.to_oklch();
+            assert!((back.l - lch.l).abs() < 1e-12, "{:?}", back);
+            assert!((back.c - lch.c).abs() < 1e-12, "{:?}", back);
+            assert!((back.h - lch.h).abs() < 1e-12, "{:?} {:?}", back, lch);
+        }
+        let lch: Oklch<f64> = Oklab::new(0.5, 0.1, -1e-12).to_oklch();
+        assert!(lch.h >= 0. && lch.h < 2. * PI, "{:?}", lch);
+    }
+
+    #[test]
+    fn test_achromatic_hue() {
+        for v in [0u8, 0x20, 0x80, 0xFF].iter() {
+            let lch: Oklch<f64> = Rgb::<u8>::new(*v, *v, *v).to_xyz::<f64>().to_oklab::<f64>().to_oklch();
+            assert_eq!(lch.h, 0., "{:?}", lch);
+            assert!(lch.c < 1e-4, "{:?}", lch);
+        }
+        let lch: Oklch<f32> = Oklab::new(0.5f32, 0., 0.).to_oklch();
+        assert_eq!(lch, Oklch::new(0.5, 0., 0.));
+    }
+}
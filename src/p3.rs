@@ -0,0 +1,155 @@
+//! P3 wide gamut spaces, sharing the DCI-P3 primaries
+
+use channel::Channel;
+use color_space::{D50, D65, Dci, Mat3, MatrixColorSpace, Srgb, TransferFunction};
+use num_traits::{Float, cast};
+use yxy::Yxy;
+
+fn red<T: Channel + Float>() -> (T, T) {
+    (0.680.to_channel(), 0.320.to_channel())
+}
+
+fn green<T: Channel + Float>() -> (T, T) {
+    (0.265.to_channel(), 0.690.to_channel())
+}
+
+fn blue<T: Channel + Float>() -> (T, T) {
+    (0.150.to_channel(), 0.060.to_channel())
+}
+
+/// Display P3 standard, the P3 primaries with a D65 white and the sRGB
+/// transfer function as used by Apple displays and CSS `color(display-p3)`.
+/// Use as `Rgb<T, DisplayP3>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DisplayP3;
+
+impl MatrixColorSpace for DisplayP3 {
+    type WhitePoint = D65;
+
+    fn red<T: Channel + Float>() -> Yxy<T, D50> {
+        let (x, y) = red();
+        Yxy::new(x, y, 0.2289746.to_channel())
+    }
+    fn green<T: Channel + Float>() -> Yxy<T, D50> {
+        let (x, y) = green();
+        Yxy::new(x, y, 0.6917385.to_channel())
+    }
+    fn blue<T: Channel + Float>() -> Yxy<T, D50> {
+        let (x, y) = blue();
+        Yxy::new(x, y, 0.0792869.to_channel())
+    }
+    fn to_xyz_matrix<T: Channel + Float>() -> Mat3<T>{
+        Mat3([
+            0.4865709.to_channel(),  0.2656677.to_channel(),  0.1982173.to_channel(),
+            0.2289746.to_channel(),  0.6917385.to_channel(),  0.0792869.to_channel(),
+            0.0000000.to_channel(),  0.0451134.to_channel(),  1.0439444.to_channel(),
+        ])
+    }
+    fn to_rgb_matrix<T: Channel + Float>() -> Mat3<T>{
+        Mat3([
+            2.4934969.to_channel(), (-0.9313836).to_channel(), (-0.4027108).to_channel(),
+            (-0.8294890).to_channel(),  1.7626641.to_channel(),  0.0236247.to_channel(),
+            0.0358458.to_channel(), (-0.0761724).to_channel(),  0.9568845.to_channel(),
+        ])
+    }
+}
+
+impl TransferFunction for DisplayP3 {
+    fn from_linear<T: Float>(x: T) -> T {
+        Srgb::from_linear(x)
+    }
+
+    fn to_linear<T: Float>(x: T) -> T {
+        Srgb::to_linear(x)
+    }
+}
+
+/// DCI-P3 standard of digital cinema projection, the P3 primaries with the
+/// greenish DCI white and a pure 2.6 gamma. Use as `Rgb<T, DciP3>`, its XYZ
+/// is relative to the `Dci` white point.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DciP3;
+
+impl MatrixColorSpace for DciP3 {
+    type WhitePoint = Dci;
+
+    fn red<T: Channel + Float>() -> Yxy<T, D50> {
+        let (x, y) = red();
+        Yxy::new(x, y, 0.2094917.to_channel())
+    }
+    fn green<T: Channel + Float>() -> Yxy<T, D50> {
+        let (x, y) = green();
+        Yxy::new(x, y, 0.7215953.to_channel())
+    }
+    fn blue<T: Channel + Float>() -> Yxy<T, D50> {
+        let (x, y) = blue();
+        Yxy::new(x, y, 0.0689131.to_channel())
+    }
+    fn to_xyz_matrix<T: Channel + Float>() -> Mat3<T>{
+        Mat3([
+            0.4451698.to_channel(),  0.2771344.to_channel(),  0.1722827.to_channel(),
+            0.2094917.to_channel(),  0.7215953.to_channel(),  0.0689131.to_channel(),
+            0.0000000.to_channel(),  0.0470606.to_channel(),  0.9073554.to_channel(),
+        ])
+    }
+    fn to_rgb_matrix<T: Channel + Float>() -> Mat3<T>{
+        Mat3([
+            2.7253940.to_channel(), (-1.0180030).to_channel(), (-0.4401632).to_channel(),
+            (-0.7951680).to_channel(),  1.6897321.to_channel(),  0.0226472.to_channel(),
+            0.0412419.to_channel(), (-0.0876390).to_channel(),  1.1009294.to_channel(),
+        ])
+    }
+}
+
+/// Pure 2.6 gamma, negative values mirror the curve.
+impl TransferFunction for DciP3 {
+    fn from_linear<T: Float>(x: T) -> T {
+        x.signum() * x.abs().powf(cast(1. / 2.6).unwrap())
+    }
+
+    fn to_linear<T: Float>(x: T) -> T {
+        x.signum() * x.abs().powf(cast(2.6).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DisplayP3, DciP3};
+    use color_space::{D65, Dci, Srgb, WhitePoint};
+    use rgb::Rgb;
+    use xyz::{Xyz, ToXyz};
+    use yxy::ToYxy;
+
+    #[test]
+    fn test_green_wider_than_srgb() {
+        // Distance of the green primary chromaticity from the white point
+        let spread = |x: f64, y: f64| (x - 0.3127).hypot(y - 0.3290);
+        let srgb = Rgb::<f64, Srgb>::new(0., 1., 0.).to_xyz::<f64>().to_yxy::<f64>();
+        let p3 = Rgb::<f64, DisplayP3>::new(0., 1., 0.).to_xyz::<f64>().to_yxy::<f64>();
+        assert!((p3.x - 0.265).abs() < 1e-6 && (p3.y - 0.690).abs() < 1e-6, "{} {}", p3.x, p3.y);
+        assert!(spread(p3.x, p3.y) > spread(srgb.x, srgb.y));
+
+        let dci = Rgb::<f64, DciP3>::new(0., 1., 0.).to_xyz::<f64>().to_yxy::<f64>();
+        assert!((dci.x - 0.265).abs() < 1e-6 && (dci.y - 0.690).abs() < 1e-6, "{} {}", dci.x, dci.y);
+    }
+
+    #[test]
+    fn test_white() {
+        let d65 = D65::xyz::<f64>();
+        let white: Xyz<f64, D65> = Rgb::<f64, DisplayP3>::new(1., 1., 1.).to_xyz();
+        assert!((white.x - d65.x).abs() < 5e-4 && (white.y - 1.).abs() < 1e-6 && (white.z - d65.z).abs() < 5e-4, "{:?}", white);
+        let dci = Dci::xyz::<f64>();
+        let white: Xyz<f64, Dci> = Rgb::<f64, DciP3>::new(1., 1., 1.).to_xyz();
+        assert!((white.x - dci.x).abs() < 1e-6 && (white.y - 1.).abs() < 1e-6 && (white.z - dci.z).abs() < 1e-6, "{:?}", white);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let rgb = Rgb::<f64, DciP3>::new(0.2, 0.5, 0.8);
+        let back = Rgb::<f64, DciP3>::from_xyz(&rgb.to_xyz::<f64>());
+        assert!((back.r - rgb.r).abs() < 1e-6 && (back.g - rgb.g).abs() < 1e-6 && (back.b - rgb.b).abs() < 1e-6, "{:?}", back);
+        let srgb = Rgb::<f64, Srgb>::new(0.2, 0.5, 0.8);
+        let p3 = Rgb::<f64, DisplayP3>::from_xyz(&srgb.to_xyz::<f64>());
+        assert!(p3.r > 0. && p3.r < 1. && p3.g > 0. && p3.g < 1. && p3.b > 0. && p3.b < 1., "{:?}", p3);
+    }
+}
@@ -0,0 +1,75 @@
+//! Property tests for the consistency of the conversion graph
+
+extern crate proptest;
+
+use self::proptest::prelude::*;
+use color_space::{D65, Srgb};
+use lab::{Lab, ToLab};
+use rgb::{Rgb, ToRgb};
+use xyz::{Xyz, ToXyz};
+use yxy::{Yxy, ToYxy};
+
+fn srgb_u8() -> impl Strategy<Value = Rgb<u8, Srgb>> {
+    (any::<u8>(), any::<u8>(), any::<u8>()).prop_map(|(r, g, b)| Rgb::new(r, g, b))
+}
+
+fn srgb_f32() -> impl Strategy<Value = Rgb<f32, Srgb>> {
+    (0f32..=1., 0f32..=1., 0f32..=1.).prop_map(|(r, g, b)| Rgb::new(r, g, b))
+}
+
+fn close(a: f32, b: f32, tolerance: f32) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+proptest! {
+    #[test]
+    fn srgb_u8_lab_round_trip(c in srgb_u8()) {
+        let lab: Lab<f32, D65> = c.to_xyz::<f32>().to_lab();
+        let back: Rgb<u8, Srgb> = lab.to_xyz::<f32>().to_rgb();
+        prop_assert_eq!(back, c);
+    }
+
+    #[test]
+    fn srgb_f32_lab_round_trip(c in srgb_f32()) {
+        let lab: Lab<f64, D65> = c.to_xyz::<f64>().to_lab();
+        let back: Rgb<f32, Srgb> = lab.to_xyz::<f64>().to_rgb();
+        prop_assert!(close(back.r, c.r, 1e-4) && close(back.g, c.g, 1e-4) && close(back.b, c.b, 1e-4),
+            "{:?} {:?}", c, back);
+    }
+
+    #[test]
+    fn srgb_u8_xyz_round_trip(c in srgb_u8()) {
+        let back: Rgb<u8, Srgb> = c.to_xyz::<f32>().to_rgb();
+        prop_assert_eq!(back, c);
+    }
+
+    #[test]
+    fn srgb_f32_xyz_round_trip(c in srgb_f32()) {
+        let back: Rgb<f32, Srgb> = c.to_xyz::<f32>().to_rgb();
+        prop_assert!(close(back.r, c.r, 1e-4) && close(back.g, c.g, 1e-4) && close(back.b, c.b, 1e-4),
+            "{:?} {:?}", c, back);
+    }
+
+    #[test]
+    fn xyz_yxy_round_trip(c in srgb_f32()) {
+        let xyz: Xyz<f64, D65> = c.to_xyz();
+        prop_assume!(xyz.y > 1e-6);
+        let yxy: Yxy<f64, D65> = xyz.to_yxy();
+        let back: Xyz<f64, D65> = yxy.to_xyz();
+        prop_assert!((back.x - xyz.x).abs() < 1e-9 && (back.y - xyz.y).abs() < 1e-9 && (back.z - xyz.z).abs() < 1e-9,
+            "{:?} {:?}", xyz, back);
+    }
+
+    #[test]
+    fn relative_luminance_is_xyz_y(c in srgb_f32()) {
+        let luminance = c.relative_luminance();
+        prop_assert!(close(luminance, c.to_xyz::<f32>().y, 1e-4), "{:?}", c);
+    }
+
+    #[test]
+    fn srgb_transfer_round_trip(c in srgb_f32()) {
+        let back = c.decode().encode_srgb();
+        prop_assert!(close(back.r, c.r, 1e-5) && close(back.g, c.g, 1e-5) && close(back.b, c.b, 1e-5),
+            "{:?} {:?}", c, back);
+    }
+}
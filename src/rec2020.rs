@@ -0,0 +1,104 @@
+//! ITU-R BT.2020, the wide gamut space of UHD and HDR video
+
+use channel::Channel;
+use color_space::{D50, D65, Mat3, MatrixColorSpace, TransferFunction};
+use num_traits::{Float, cast};
+use yxy::Yxy;
+
+/// Rec. 2020 standard with the BT.2020 transfer function, use as
+/// `Rgb<T, Rec2020>`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rec2020;
+
+impl MatrixColorSpace for Rec2020 {
+    type WhitePoint = D65;
+
+    fn red<T: Channel + Float>() -> Yxy<T, D50> {
+        Yxy::new(0.708.to_channel(), 0.292.to_channel(), 0.2627002.to_channel())
+    }
+    fn green<T: Channel + Float>() -> Yxy<T, D50> {
+        Yxy::new(0.170.to_channel(), 0.797.to_channel(), 0.6779981.to_channel())
+    }
+    fn blue<T: Channel + Float>() -> Yxy<T, D50> {
+        Yxy::new(0.131.to_channel(), 0.046.to_channel(), 0.0593017.to_channel())
+    }
+    fn to_xyz_matrix<T: Channel + Float>() -> Mat3<T>{
+        Mat3([
+            0.6369580.to_channel(),  0.1446169.to_channel(),  0.1688810.to_channel(),
+            0.2627002.to_channel(),  0.6779981.to_channel(),  0.0593017.to_channel(),
+            0.0000000.to_channel(),  0.0280727.to_channel(),  1.0609851.to_channel(),
+        ])
+    }
+    fn to_rgb_matrix<T: Channel + Float>() -> Mat3<T>{
+        Mat3([
+            1.7166512.to_channel(), (-0.3556708).to_channel(), (-0.2533663).to_channel(),
+            (-0.6666844).to_channel(),  1.6164812.to_channel(),  0.0157685.to_channel(),
+            0.0176399.to_channel(), (-0.0427706).to_channel(),  0.9421031.to_channel(),
+        ])
+    }
+}
+
+// BT.2020 OETF constants for 12 bit precision
+const ALPHA: f64 = 1.09929682680944;
+const BETA: f64 = 0.018053968510807;
+
+/// The BT.2020 camera curve, a 0.45 power with a linear toe.
+impl TransferFunction for Rec2020 {
+    fn from_linear<T: Float>(x: T) -> T {
+        if x >= cast(BETA).unwrap() {
+            cast::<f64, T>(ALPHA).unwrap() * x.powf(cast(0.45).unwrap()) - cast(ALPHA - 1.).unwrap()
+        }else{
+            cast::<f64, T>(4.5).unwrap() * x
+        }
+    }
+
+    fn to_linear<T: Float>(x: T) -> T {
+        if x >= cast(4.5 * BETA).unwrap() {
+            ((x + cast(ALPHA - 1.).unwrap()) / cast(ALPHA).unwrap()).powf(cast(1. / 0.45).unwrap())
+        }else{
+            x / cast(4.5).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rec2020;
+    use color_space::{MatrixColorSpace, Srgb, TransferFunction};
+    use rgb::Rgb;
+    use xyz::ToXyz;
+    use yxy::ToYxy;
+
+    #[test]
+    fn test_red_outside_srgb() {
+        let red = Rgb::<f64, Rec2020>::new(1., 0., 0.).to_xyz::<f64>().to_yxy::<f64>();
+        assert!((red.x - 0.708).abs() < 1e-6 && (red.y - 0.292).abs() < 1e-6, "{} {}", red.x, red.y);
+
+        // Same side of every edge of the sRGB triangle as its third vertex
+        let (r, g, b) = (Srgb::red::<f64>(), Srgb::green::<f64>(), Srgb::blue::<f64>());
+        let side = |p: (f64, f64), a: (f64, f64), b: (f64, f64)| (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0);
+        let inside = |p: (f64, f64)| {
+            let (r, g, b) = ((r.x, r.y), (g.x, g.y), (b.x, b.y));
+            side(p, r, g) * side(b, r, g) >= 0. && side(p, g, b) * side(r, g, b) >= 0. && side(p, b, r) * side(g, b, r) >= 0.
+        };
+        assert!(inside((0.3127, 0.3290)));
+        assert!(!inside((red.x, red.y)));
+    }
+
+    #[test]
+    fn test_transfer_function() {
+        for &x in &[0f64, 0.01, 0.018053968510807, 0.1, 0.5, 1.] {
+            let encoded = Rec2020::from_linear(x);
+            assert!((Rec2020::to_linear(encoded) - x).abs() < 1e-12, "{}", x);
+        }
+        assert!((Rec2020::from_linear(1f64) - 1.).abs() < 1e-12);
+        assert!((Rec2020::from_linear(0.01f64) - 0.045).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_xyz_round_trip() {
+        let rgb = Rgb::<f64, Rec2020>::new(0.2, 0.5, 0.8);
+        let back = Rgb::<f64, Rec2020>::from_xyz(&rgb.to_xyz::<f64>());
+        assert!((back.r - rgb.r).abs() < 1e-6 && (back.g - rgb.g).abs() < 1e-6 && (back.b - rgb.b).abs() < 1e-6, "{:?}", back);
+    }
+}
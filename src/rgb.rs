@@ -13,20 +13,28 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use num_traits::{self, Zero, Saturating, NumCast, Num, Float};
+use num_traits::{self, Zero, Saturating, NumCast, Num, Float, zero, one};
 use std::{borrow::{Borrow, BorrowMut}, ops::{Mul, Div, Add, Sub, Index, IndexMut}};
 use std::marker::PhantomData;
 use std::mem;
-use color_space::{TransferFunction, Srgb, LinearRgb, MatrixColorSpace, D65, Vec3};
+use std::sync::OnceLock;
+use color_space::{TransferFunction, Srgb, LinearRgb, MatrixColorSpace, WhitePoint, D65, Vec3, bradford_matrix};
 use angle::*;
 
 use {Color, FloatColor};
-use {Channel, FloatChannel};
+use {Channel, FloatChannel, RoundingMode};
+use error::ParseError;
 use {Hsv, ToHsv};
+use Hsl;
 use {Luma, ToLuma};
 use xyz::{Xyz, ToXyz};
+use lab::ToLab;
 use alpha::{ToRgba, Rgba};
+use hue::normalize_degrees;
 use std::fmt::{self, Debug};
+use std::str::FromStr;
+#[cfg(feature="rand")]
+use rand::Rng;
 
 #[derive(Serialize, Deserialize)]
 pub struct Rgb<T = u8, S = Srgb> { pub r: T, pub g: T, pub b: T, standard: PhantomData<S> }
@@ -39,14 +47,14 @@ impl<T: Clone,S> Clone for Rgb<T, S>{
 
 impl<T: Copy, S> Copy for Rgb<T, S>{}
 
-impl<N: Clone + PartialEq + Num + NumCast, S> PartialEq for Rgb<N, S>{
+impl<N: PartialEq, S> PartialEq for Rgb<N, S>{
 	#[inline]
 	fn eq(&self, other: &Rgb<N, S>) -> bool{
 		self.r.eq(&other.r) && self.g.eq(&other.g) && self.b.eq(&other.b)
 	}
 }
 
-impl<N: Clone + PartialEq + Eq + Num + NumCast, S> Eq for Rgb<N, S>{}
+impl<N: Eq, S> Eq for Rgb<N, S>{}
 
 impl<T: Debug, S: Default + Debug> Debug for Rgb<T,S>{
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -79,6 +87,114 @@ impl<T, S> Rgb<T, S> {
     }
 }
 
+impl<T: Copy, S> Rgb<T, S> {
+    /// Color with all channels set to `v`, a gray.
+    #[inline]
+    pub const fn splat(v: T) -> Rgb<T, S> {
+        Rgb::new(v, v, v)
+    }
+}
+
+impl<T: Channel, S> Rgb<T, S> {
+    /// Largest of the three channels, the HSV value.
+    #[inline]
+    pub fn max_channel(&self) -> T {
+        let rg = if self.r > self.g { self.r } else { self.g };
+        if rg > self.b { rg } else { self.b }
+    }
+
+    /// Smallest of the three channels.
+    #[inline]
+    pub fn min_channel(&self) -> T {
+        let rg = if self.r < self.g { self.r } else { self.g };
+        if rg < self.b { rg } else { self.b }
+    }
+
+    /// Difference between the largest and smallest channels, 0 for grays.
+    #[inline]
+    pub fn chroma(&self) -> T
+    where T: Sub<Output = T>
+    {
+        self.max_channel() - self.min_channel()
+    }
+
+    /// Clamps each channel between the matching channels of `min` and `max`,
+    /// like GLSL `clamp(vec3, vec3, vec3)`. Same as `Color::clamp_c` without
+    /// needing the trait in scope.
+    #[inline]
+    pub fn clamp_to(&self, min: &Rgb<T, S>, max: &Rgb<T, S>) -> Rgb<T, S> {
+        Rgb::new(self.r.clamp(min.r, max.r),
+                 self.g.clamp(min.g, max.g),
+                 self.b.clamp(min.b, max.b))
+    }
+
+    /// Channels as normalized `[0, 1]` floats whatever the storage type, so
+    /// colors of different channel types can be compared when debugging.
+    /// Values outside `[0, 1]` are printed as is.
+    pub fn debug_normalized(&self) -> String {
+        format!("Rgb({:.3}, {:.3}, {:.3})", self.r.to_channel_f64(), self.g.to_channel_f64(), self.b.to_channel_f64())
+    }
+}
+
+impl<S> Rgb<u8, S> {
+    /// Parses a `#rrggbb` or short `#rgb` hex string, where `#abc` stands for
+    /// `#aabbcc`. The `#` is optional and digits are case insensitive.
+    pub fn from_hex_str(hex: &str) -> Result<Rgb<u8, S>, ParseError> {
+        let [r, g, b, _] = parse_hex_channels(hex, false)?;
+        Ok(Rgb::new(r, g, b))
+    }
+}
+
+// Parses 3 or 6 hex digits, and with `alpha` also 4 or 8 where the last
+// channel is the alpha, into rgba channels. Opaque without an alpha digit.
+pub(crate) fn parse_hex_channels(hex: &str, alpha: bool) -> Result<[u8; 4], ParseError> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    let mut digits = [0u8; 8];
+    let mut len = 0;
+    for c in hex.chars() {
+        let d = c.to_digit(16).ok_or(ParseError::InvalidDigit(c))?;
+        if len < digits.len() {
+            digits[len] = d as u8;
+        }
+        len += 1;
+    }
+    let short = match len {
+        3 => true,
+        6 => false,
+        4 | 8 if alpha => len == 4,
+        len => return Err(ParseError::InvalidLength(len)),
+    };
+    let channel = |i: usize| if short { digits[i] * 17 } else { digits[2 * i] << 4 | digits[2 * i + 1] };
+    let a = if len == 4 || len == 8 { channel(3) } else { 0xFF };
+    Ok([channel(0), channel(1), channel(2), a])
+}
+
+/// Parses a hex color, see `from_hex_str`, or else a CSS color name like
+/// `"red"`, see `consts::by_name`.
+impl FromStr for Rgb<u8, Srgb> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Rgb<u8, Srgb>, ParseError> {
+        match Rgb::from_hex_str(s) {
+            Ok(c) => Ok(c),
+            Err(e) if s.starts_with('#') => Err(e),
+            Err(_) => consts::by_name(s).ok_or(ParseError::UnknownName),
+        }
+    }
+}
+
+impl<S> Rgb<u8, S> {
+    /// Lowercase `#rrggbb` hex string.
+    pub fn to_hex_string(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+
+    /// Uppercase `#RRGGBB` hex string.
+    pub fn to_hex_string_upper(&self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+}
+
 impl<T: Channel, S: TransferFunction> Rgb<T, S> {
     pub fn from_hex(hex: u32) -> Rgb<T, S> {
         let r = hex >> 16 & 0xFF;
@@ -160,12 +276,493 @@ impl<T: Channel, S: TransferFunction> Rgb<T, S> {
         }
     }
 
+    /// Decodes into linear RGB allowing extended range values, so an sRGB
+    /// channel of 1.2 decodes to more than 1. Use `to_linear_clamped` when
+    /// the result has to stay in `[0, 1]`.
     pub fn to_linear(&self) -> Rgb<T, LinearRgb>{
         let r = S::to_linear(self.r.to_nearest_precision_float());
         let g = S::to_linear(self.g.to_nearest_precision_float());
         let b = S::to_linear(self.b.to_nearest_precision_float());
         Rgb::new(r.to_channel(), g.to_channel(), b.to_channel())
     }
+
+    /// Decodes into linear RGB clamping the input channels to `[0, 1]` first.
+    pub fn to_linear_clamped(&self) -> Rgb<T, LinearRgb>{
+        let clamp = |c: T| {
+            let c = c.to_nearest_precision_float();
+            S::to_linear(c.max(zero()).min(one()))
+        };
+        Rgb::new(clamp(self.r).to_channel(), clamp(self.g).to_channel(), clamp(self.b).to_channel())
+    }
+}
+
+// Luminance of linear Rec. 709 / sRGB primaries
+fn rec709_luminance(r: f64, g: f64, b: f64) -> f64 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+impl<T: Channel> Rgb<T, Srgb> {
+    /// Decodes the sRGB transfer function into linear RGB.
+    #[inline]
+    pub fn decode(&self) -> Rgb<T, LinearRgb> {
+        self.to_linear()
+    }
+
+    /// WCAG relative luminance, the Rec. 709 weighted sum of the linear
+    /// channels. Scaled to the range of `T` like a channel, from 0 for black
+    /// to 1 for white with floats or 0 to 255 with `u8`.
+    pub fn relative_luminance(&self) -> T {
+        self.relative_luminance_f64().to_channel()
+    }
+
+    fn relative_luminance_f64(&self) -> f64 {
+        let linear = |c: T| Srgb::to_linear(c.to_channel_f64());
+        rec709_luminance(linear(self.r), linear(self.g), linear(self.b))
+    }
+
+    /// Desaturates to the gray of the same relative luminance, so brightness
+    /// is preserved as perceived. Computed in linear light and re-encoded.
+    pub fn to_grayscale(&self) -> Rgb<T, Srgb> {
+        Rgb::splat(Srgb::from_linear(self.relative_luminance_f64()).to_channel())
+    }
+
+    /// Adds both colors as light in XYZ, so red and green light make yellow.
+    ///
+    /// Unlike `mix` or alpha blending this doesn't interpolate between the
+    /// colors. Results brighter than the gamut are scaled down keeping their
+    /// chromaticity.
+    pub fn mix_as_light(&self, other: &Rgb<T, Srgb>) -> Rgb<T, Srgb> {
+        let xyz = self.to_xyz::<f64>().add_light(&other.to_xyz());
+        let rgb = Srgb::to_rgb_matrix() * xyz.into();
+        let max = rgb[0].max(rgb[1]).max(rgb[2]);
+        let scale = if max > 1. { 1. / max } else { 1. };
+        Rgb::new(
+            Srgb::from_linear((rgb[0] * scale).max(0.)).to_channel(),
+            Srgb::from_linear((rgb[1] * scale).max(0.)).to_channel(),
+            Srgb::from_linear((rgb[2] * scale).max(0.)).to_channel(),
+        )
+    }
+}
+
+impl<S> Rgb<u8, S> {
+    /// Packs the color as RGBX8 with an opaque padding byte.
+    #[inline]
+    pub fn to_rgbx(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, 0xFF]
+    }
+
+    /// Unpacks an RGBX8 color ignoring the padding byte.
+    #[inline]
+    pub fn from_rgbx(rgbx: [u8; 4]) -> Rgb<u8, S> {
+        Rgb::new(rgbx[0], rgbx[1], rgbx[2])
+    }
+
+    /// True if every channel differs from `other` by at most `tolerance` levels.
+    pub fn within(&self, other: &Rgb<u8, S>, tolerance: u8) -> bool {
+        self.r.abs_diff(other.r) <= tolerance
+            && self.g.abs_diff(other.g) <= tolerance
+            && self.b.abs_diff(other.b) <= tolerance
+    }
+}
+
+// RGB to and from the RYB color wheel. Removes the white component, moves
+// green between yellow and blue and rescales to keep the largest channel.
+fn rgb_to_ryb(rgb: [f32; 3]) -> [f32; 3] {
+    let [mut r, mut g, mut b] = rgb;
+    let white = r.min(g).min(b);
+    r -= white;
+    g -= white;
+    b -= white;
+    let max_green = r.max(g).max(b);
+    let mut y = r.min(g);
+    r -= y;
+    g -= y;
+    if b > 0. && g > 0. {
+        b /= 2.;
+        g /= 2.;
+    }
+    y += g;
+    b += g;
+    let max_yellow = r.max(y).max(b);
+    if max_yellow > 0. {
+        let n = max_green / max_yellow;
+        r *= n;
+        y *= n;
+        b *= n;
+    }
+    [r + white, y + white, b + white]
+}
+
+fn ryb_to_rgb(ryb: [f32; 3]) -> [f32; 3] {
+    let [mut r, mut y, mut b] = ryb;
+    let white = r.min(y).min(b);
+    r -= white;
+    y -= white;
+    b -= white;
+    let max_yellow = r.max(y).max(b);
+    let mut g = y.min(b);
+    y -= g;
+    b -= g;
+    if b > 0. && g > 0. {
+        b *= 2.;
+        g *= 2.;
+    }
+    r += y;
+    g += y;
+    let max_green = r.max(g).max(b);
+    if max_green > 0. {
+        let n = max_yellow / max_green;
+        r *= n;
+        g *= n;
+        b *= n;
+    }
+    [r + white, g + white, b + white]
+}
+
+/// Perceived warmth of a color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Temperature {
+    /// Reds, oranges and yellows.
+    Warm,
+    /// Greens, blues and violets.
+    Cool,
+    /// Grays and colors too desaturated to have a clear hue.
+    Neutral,
+}
+
+/// Coarse named bucket a color falls in, for tagging and filtering.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorCategory {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Cyan,
+    Blue,
+    Purple,
+    Pink,
+    Gray,
+    Black,
+    White,
+}
+
+impl Rgb<u8, Srgb> {
+    /// Classifies the color as warm or cool by its HSV hue, warm hues go from
+    /// magenta-red (330°) through yellow up to 90°.
+    pub fn temperature(&self) -> Temperature {
+        const NEUTRAL_CHROMA: u8 = 16;
+        if self.chroma() < NEUTRAL_CHROMA {
+            return Temperature::Neutral;
+        }
+        let h = self.to_hsv::<f32>().h.0;
+        if !(90. ..330.).contains(&h) {
+            Temperature::Warm
+        }else{
+            Temperature::Cool
+        }
+    }
+
+    /// Buckets the color by its HSV hue. Very dark colors are `Black` and
+    /// colors too desaturated to have a clear hue are `Gray` or `White`.
+    pub fn category(&self) -> ColorCategory {
+        const BLACK_MAX: u8 = 0x30;
+        const WHITE_MIN: u8 = 0xE0;
+        const NEUTRAL_CHROMA: u8 = 16;
+        let max = self.max_channel();
+        if max < BLACK_MAX {
+            return ColorCategory::Black;
+        }
+        if self.chroma() < NEUTRAL_CHROMA {
+            return if self.min_channel() >= WHITE_MIN { ColorCategory::White } else { ColorCategory::Gray };
+        }
+        match self.to_hsv::<f32>().h.0 {
+            h if h < 15. => ColorCategory::Red,
+            h if h < 45. => ColorCategory::Orange,
+            h if h < 70. => ColorCategory::Yellow,
+            h if h < 160. => ColorCategory::Green,
+            h if h < 200. => ColorCategory::Cyan,
+            h if h < 260. => ColorCategory::Blue,
+            h if h < 290. => ColorCategory::Purple,
+            h if h < 345. => ColorCategory::Pink,
+            _ => ColorCategory::Red,
+        }
+    }
+
+    #[inline]
+    pub fn is_warm(&self) -> bool {
+        self.temperature() == Temperature::Warm
+    }
+
+    #[inline]
+    pub fn is_cool(&self) -> bool {
+        self.temperature() == Temperature::Cool
+    }
+
+    /// Builds a color from HSV components, hue in degrees and saturation and
+    /// value in `[0, 1]`. The hue wraps around and the rest are clamped.
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Rgb<u8, Srgb> {
+        Hsv::<f32, Srgb>::new(Deg(normalize_degrees(h)), s.clamp(0., 1.), v.clamp(0., 1.)).to_rgb()
+    }
+
+    /// Builds a color from HSL components, hue in degrees and saturation and
+    /// lightness in `[0, 1]`. The hue wraps around and the rest are clamped.
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Rgb<u8, Srgb> {
+        Hsl::<f32, Srgb>::new(Deg(normalize_degrees(h)), s.clamp(0., 1.), l.clamp(0., 1.)).to_rgb()
+    }
+
+    // Index of the palette color with the smallest Lab distance to self
+    fn nearest_by_lab<I: Iterator<Item = Rgb<u8, Srgb>>>(&self, palette: I) -> usize {
+        let lab = self.to_xyz::<f32>().to_lab::<f32>();
+        let mut closest = 0;
+        let mut min = f32::INFINITY;
+        for (i, c) in palette.enumerate() {
+            let other = c.to_xyz::<f32>().to_lab::<f32>();
+            let d = (lab.l - other.l).powi(2) + (lab.a - other.a).powi(2) + (lab.b - other.b).powi(2);
+            if d < min {
+                min = d;
+                closest = i;
+            }
+        }
+        closest
+    }
+
+    /// Index of the closest of the 16 basic ANSI terminal colors, bright
+    /// variants being 8 to 15, by Lab distance to the xterm palette.
+    pub fn to_ansi_16(&self) -> u8 {
+        const XTERM: [u32; 16] = [
+            0x000000, 0xcd0000, 0x00cd00, 0xcdcd00, 0x0000ee, 0xcd00cd, 0x00cdcd, 0xe5e5e5,
+            0x7f7f7f, 0xff0000, 0x00ff00, 0xffff00, 0x5c5cff, 0xff00ff, 0x00ffff, 0xffffff,
+        ];
+        self.nearest_by_lab(XTERM.iter().map(|hex| Rgb::from_hex(*hex))) as u8
+    }
+
+    /// Snaps each channel to the closest multiple of 0x33, giving one of the
+    /// 216 web safe colors.
+    pub fn to_web_safe(&self) -> Rgb<u8, Srgb> {
+        let snap = |c: u8| ((c as u32 + 25) / 51 * 51) as u8;
+        Rgb::new(snap(self.r), snap(self.g), snap(self.b))
+    }
+
+    /// Closest of the 216 web safe colors by Lab distance, which can differ
+    /// from snapping each channel.
+    pub fn to_web_safe_perceptual(&self) -> Rgb<u8, Srgb> {
+        let web_safe = |i: usize| Rgb::new((i / 36 * 51) as u8, (i / 6 % 6 * 51) as u8, (i % 6 * 51) as u8);
+        web_safe(self.nearest_by_lab((0..216).map(web_safe)))
+    }
+
+    /// Mixes the colors like paints, so blue and yellow make green instead of
+    /// gray. Interpolates in the painters' red, yellow, blue color wheel
+    /// (RYB), a perceptual approximation rather than a physical pigment model.
+    pub fn mix_subtractive(&self, other: &Rgb<u8, Srgb>, t: f32) -> Rgb<u8, Srgb> {
+        let t = t.clamp(0., 1.);
+        let c1 = rgb_to_ryb(Vec3::from(self.to_rgb::<f32>()).0);
+        let c2 = rgb_to_ryb(Vec3::from(other.to_rgb::<f32>()).0);
+        let ryb = [
+            c1[0] + (c2[0] - c1[0]) * t,
+            c1[1] + (c2[1] - c1[1]) * t,
+            c1[2] + (c2[2] - c1[2]) * t,
+        ];
+        let [r, g, b] = ryb_to_rgb(ryb);
+        Rgb::<f32, Srgb>::new(r, g, b).to_rgb()
+    }
+
+    /// WCAG 2 contrast ratio between both colors, from 1 for identical
+    /// luminances to 21 for black on white.
+    pub fn contrast_against(&self, other: &Rgb<u8, Srgb>) -> f32 {
+        contrast_ratio(&self.to_rgb::<f32>(), &other.to_rgb::<f32>())
+    }
+
+    /// Whether text in this color over `other`, or the other way around,
+    /// passes WCAG AA: a contrast of 4.5:1, or 3:1 for large text.
+    pub fn meets_wcag_aa(&self, other: &Rgb<u8, Srgb>, large_text: bool) -> bool {
+        self.contrast_against(other) >= if large_text { 3. } else { 4.5 }
+    }
+
+    /// Whether text in this color over `other`, or the other way around,
+    /// passes WCAG AAA: a contrast of 7:1, or 4.5:1 for large text.
+    pub fn meets_wcag_aaa(&self, other: &Rgb<u8, Srgb>, large_text: bool) -> bool {
+        self.contrast_against(other) >= if large_text { 4.5 } else { 7. }
+    }
+
+    /// Lab chroma of the color, its distance from the neutral gray axis.
+    pub fn gray_axis_distance(&self) -> f32 {
+        self.to_xyz::<f32>().to_lab::<f32>().chromacity()
+    }
+
+    /// Whether the Lab chroma of the color is at most `tolerance`. A
+    /// tolerance around 2 is hard to tell apart from gray.
+    #[inline]
+    pub fn is_neutral(&self, tolerance: f32) -> bool {
+        self.gray_axis_distance() <= tolerance
+    }
+}
+
+// 8 bit sRGB decoding table, built on first use. OnceLock makes the
+// initialization safe when several threads hit it at once.
+fn srgb_to_linear_lut() -> &'static [f32; 256] {
+    static LUT: OnceLock<[f32; 256]> = OnceLock::new();
+    LUT.get_or_init(|| {
+        let mut lut = [0.; 256];
+        for (i, l) in lut.iter_mut().enumerate() {
+            *l = Srgb::to_linear((i as u8).to_channel_f32());
+        }
+        lut
+    })
+}
+
+impl Rgb<u8, Srgb> {
+    /// Decodes into linear floating point RGB using a lookup table.
+    #[inline]
+    pub fn to_linear_f32(&self) -> Rgb<f32, LinearRgb> {
+        let lut = srgb_to_linear_lut();
+        Rgb::new(lut[self.r as usize], lut[self.g as usize], lut[self.b as usize])
+    }
+
+    /// Decodes into 16 bit linear RGB, rounding to the nearest level. Keeps
+    /// the dark tones that 8 bit linear would band.
+    pub fn to_linear_u16(&self) -> Rgb<u16, LinearRgb> {
+        let lut = srgb_to_linear_lut();
+        let decode = |c: u8| (lut[c as usize] as f64 * u16::MAX as f64).round() as u16;
+        Rgb::new(decode(self.r), decode(self.g), decode(self.b))
+    }
+
+    /// Encodes 16 bit linear RGB into 8 bit sRGB, rounding to the nearest
+    /// level.
+    pub fn from_linear_u16(linear: Rgb<u16, LinearRgb>) -> Rgb<u8, Srgb> {
+        let encode = |c: u16| {
+            (Srgb::from_linear(c as f64 / u16::MAX as f64) * u8::MAX as f64).round() as u8
+        };
+        Rgb::new(encode(linear.r), encode(linear.g), encode(linear.b))
+    }
+}
+
+impl Rgb<u8, Srgb> {
+    /// Quantizes the relative luminance to `levels` gray levels evenly spaced
+    /// in sRGB so the steps are perceptually uniform.
+    ///
+    /// # Panics
+    ///
+    /// If `levels` is below 2, there's no quantizing to fewer levels.
+    pub fn to_gray_levels(&self, levels: u8) -> Rgb<u8, Srgb> {
+        assert!(levels >= 2, "Quantizing needs at least 2 levels");
+        let steps = (levels - 1) as f64;
+        let gray = ((Srgb::from_linear(self.relative_luminance_f64()) * steps).round() / steps).to_channel();
+        Rgb::new(gray, gray, gray)
+    }
+
+    /// White balances the color with a von Kries style diagonal scaling in
+    /// linear RGB that turns `measured_white`, e.g. a gray card in the same
+    /// image, into a neutral gray of the same luminance.
+    pub fn white_balance(&self, measured_white: Rgb<u8, Srgb>) -> Rgb<u8, Srgb> {
+        let white = measured_white.to_rgb::<f32>().to_linear();
+        let y = rec709_luminance(white.r as f64, white.g as f64, white.b as f64) as f32;
+        let gain = |w: f32| if w > 0. { y / w } else { 1. };
+        let linear = self.to_rgb::<f32>().to_linear();
+        let balanced = Rgb::<f32, LinearRgb>::new(
+            (linear.r * gain(white.r)).clamp(0., 1.),
+            (linear.g * gain(white.g)).clamp(0., 1.),
+            (linear.b * gain(white.b)).clamp(0., 1.),
+        );
+        balanced.encode_srgb().to_rgb()
+    }
+
+    /// Weighted mean of the colors computed in XYZ, where light adds up, so
+    /// the luminance of the mean is the weighted mean of their luminances.
+    /// Black if the weights sum to 0.
+    pub fn luminance_weighted_mean(colors: &[(Rgb<u8, Srgb>, f32)]) -> Rgb<u8, Srgb> {
+        let total: f64 = colors.iter().map(|(_, w)| *w as f64).sum();
+        if total == 0. {
+            return Rgb::new(0, 0, 0);
+        }
+        let xyz: Xyz<f64, D65> = colors.iter()
+            .map(|(c, w)| {
+                let xyz = c.to_xyz::<f64>();
+                let w = *w as f64 / total;
+                Xyz::new(xyz.x * w, xyz.y * w, xyz.z * w)
+            })
+            .sum();
+        xyz.to_rgb()
+    }
+
+    /// Adapts the color shown on a display calibrated to the `from` white
+    /// point to how it'd look on one calibrated to `to`, with a Bradford
+    /// adaptation in XYZ.
+    pub fn adapt_display<Src: WhitePoint, Dst: WhitePoint>(&self, _from: Src, _to: Dst) -> Rgb<u8, Srgb> {
+        let linear = Vec3::from(self.to_rgb::<f64>().to_linear());
+        let xyz = Srgb::to_xyz_matrix() * linear;
+        let adapted = bradford_matrix::<Src, Dst, f64>() * xyz;
+        let [r, g, b] = (Srgb::to_rgb_matrix() * adapted).0;
+        let linear = Rgb::<f64, LinearRgb>::new(r.clamp(0., 1.), g.clamp(0., 1.), b.clamp(0., 1.));
+        linear.encode_srgb().to_rgb()
+    }
+}
+
+#[cfg(feature="rand")]
+impl Rgb<u8, Srgb> {
+    /// Uniformly distributed random color, seed the rng for reproducible colors.
+    pub fn random<R: Rng>(rng: &mut R) -> Rgb<u8, Srgb> {
+        Rgb::new(rng.gen(), rng.gen(), rng.gen())
+    }
+
+    /// Random hue with a fixed saturation and lightness of `hsl(h, 70%, 60%)`.
+    pub fn random_hue<R: Rng>(rng: &mut R) -> Rgb<u8, Srgb> {
+        // hsl(h, 70%, 60%) expressed as hsv
+        Hsv::<f32, Srgb>::new(Deg(rng.gen_range(0., 360.)), 0.636, 0.88).to_rgb()
+    }
+}
+
+impl<S> Rgb<f32, S> {
+    /// Quantizes to 8 bits with an explicit rounding mode, `to_rgb` always
+    /// rounds ties up.
+    pub fn to_u8_with(&self, mode: RoundingMode) -> Rgb<u8, S> {
+        let quantize = |c: f32| mode.round(c.clamp(0., 1.) * u8::MAX as f32) as u8;
+        Rgb::new(quantize(self.r), quantize(self.g), quantize(self.b))
+    }
+
+    /// Quantizes to 8 bits with a 4x4 Bayer ordered dither at pixel `(x, y)`,
+    /// trading the banding of smooth gradients for a fine regular pattern.
+    pub fn to_u8_dithered(&self, x: u32, y: u32) -> Rgb<u8, S> {
+        const BAYER_4X4: [u8; 16] = [
+             0,  8,  2, 10,
+            12,  4, 14,  6,
+             3, 11,  1,  9,
+            15,  7, 13,  5,
+        ];
+        let threshold = (BAYER_4X4[(y % 4 * 4 + x % 4) as usize] as f32 + 0.5) / 16.;
+        let quantize = |c: f32| (c.clamp(0., 1.) * u8::MAX as f32 + threshold).floor().min(u8::MAX as f32) as u8;
+        Rgb::new(quantize(self.r), quantize(self.g), quantize(self.b))
+    }
+}
+
+impl Rgb<f32, LinearRgb> {
+    /// Scales the light by `2^stops`.
+    #[inline]
+    pub fn apply_exposure(&self, stops: f32) -> Rgb<f32, LinearRgb> {
+        let k = stops.exp2();
+        Rgb::new(self.r * k, self.g * k, self.b * k)
+    }
+
+    /// Raises each channel to `gamma`, negative values mirror the curve.
+    #[inline]
+    pub fn apply_gamma(&self, gamma: f32) -> Rgb<f32, LinearRgb> {
+        let pow = |c: f32| c.signum() * c.abs().powf(gamma);
+        Rgb::new(pow(self.r), pow(self.g), pow(self.b))
+    }
+}
+
+impl<T: Channel> Rgb<T, LinearRgb> {
+    /// Encodes linear RGB with the sRGB transfer function.
+    #[inline]
+    pub fn encode_srgb(&self) -> Rgb<T, Srgb> {
+        self.to_standard()
+    }
+
+    /// Replaces every channel with the Rec. 709 weighted luminance, staying
+    /// in linear light.
+    pub fn to_grayscale(&self) -> Rgb<T, LinearRgb> {
+        let y = rec709_luminance(self.r.to_channel_f64(), self.g.to_channel_f64(), self.b.to_channel_f64());
+        Rgb::splat(y.to_channel())
+    }
 }
 
 #[macro_export]
@@ -200,6 +797,119 @@ macro_rules! rgb_linear{
     };
 }
 
+/// Splits a slice of colors into separate r, g and b planes.
+pub fn deinterleave<T: Copy, S>(src: &[Rgb<T, S>], r: &mut [T], g: &mut [T], b: &mut [T]) {
+    assert!(r.len() == src.len() && g.len() == src.len() && b.len() == src.len(),
+        "Planes must have the same length as the source");
+    for (i, c) in src.iter().enumerate() {
+        r[i] = c.r;
+        g[i] = c.g;
+        b[i] = c.b;
+    }
+}
+
+/// Merges separate r, g and b planes into a slice of colors.
+pub fn interleave<T: Copy, S>(r: &[T], g: &[T], b: &[T], dst: &mut [Rgb<T, S>]) {
+    assert!(r.len() == dst.len() && g.len() == dst.len() && b.len() == dst.len(),
+        "Planes must have the same length as the destination");
+    for (i, c) in dst.iter_mut().enumerate() {
+        *c = Rgb::new(r[i], g[i], b[i]);
+    }
+}
+
+/// Decodes a slice of 8 bit sRGB colors into linear light, writing into `dst`.
+pub fn srgb_slice_to_linear_into(src: &[Rgb<u8, Srgb>], dst: &mut [Rgb<f32, LinearRgb>]) {
+    assert_eq!(src.len(), dst.len(), "The destination must have the same length as the source");
+    for (c, linear) in src.iter().zip(dst.iter_mut()) {
+        *linear = c.to_rgb::<f32>().to_linear();
+    }
+}
+
+/// Decodes a slice of 8 bit sRGB colors into linear light.
+pub fn srgb_slice_to_linear(src: &[Rgb<u8, Srgb>]) -> Vec<Rgb<f32, LinearRgb>> {
+    let mut dst = vec![Rgb::new(0., 0., 0.); src.len()];
+    srgb_slice_to_linear_into(src, &mut dst);
+    dst
+}
+
+/// Encodes a slice of linear colors into 8 bit sRGB, writing into `dst`.
+pub fn linear_slice_to_srgb_into(src: &[Rgb<f32, LinearRgb>], dst: &mut [Rgb<u8, Srgb>]) {
+    assert_eq!(src.len(), dst.len(), "The destination must have the same length as the source");
+    for (linear, c) in src.iter().zip(dst.iter_mut()) {
+        *c = linear.encode_srgb().to_rgb();
+    }
+}
+
+/// Encodes a slice of linear colors into 8 bit sRGB.
+pub fn linear_slice_to_srgb(src: &[Rgb<f32, LinearRgb>]) -> Vec<Rgb<u8, Srgb>> {
+    let mut dst = vec![Rgb::new(0, 0, 0); src.len()];
+    linear_slice_to_srgb_into(src, &mut dst);
+    dst
+}
+
+/// WCAG 2 contrast ratio between both colors, `(L_lighter + 0.05) /
+/// (L_darker + 0.05)` from their relative luminances. Goes from 1 for
+/// identical luminances to 21 for black on white.
+pub fn contrast_ratio<T: Channel + Float>(a: &Rgb<T, Srgb>, b: &Rgb<T, Srgb>) -> T {
+    let (l1, l2) = (a.relative_luminance(), b.relative_luminance());
+    let flare: T = cast(0.05);
+    (l1.max(l2) + flare) / (l1.min(l2) + flare)
+}
+
+/// APCA lightness contrast Lc of `text` over `bg`, the contrast metric of
+/// the WCAG 3 draft, in roughly `[-108, 106]`. Positive for dark text on a
+/// light background, negative for light text on a dark one. Unlike WCAG 2
+/// ratios it rates dark mode pairs sensibly.
+/// See https://github.com/Myndex/apca-w3 (version 0.0.98G-4g)
+pub fn apca_contrast(text: Rgb<u8, Srgb>, bg: Rgb<u8, Srgb>) -> f32 {
+    const MAIN_TRC: f64 = 2.4;
+    const NORM_BG: f64 = 0.56;
+    const NORM_TXT: f64 = 0.57;
+    const REV_TXT: f64 = 0.62;
+    const REV_BG: f64 = 0.65;
+    const BLK_THRS: f64 = 0.022;
+    const BLK_CLMP: f64 = 1.414;
+    const SCALE: f64 = 1.14;
+    const LO_OFFSET: f64 = 0.027;
+    const DELTA_Y_MIN: f64 = 0.0005;
+    const LO_CLIP: f64 = 0.1;
+
+    // APCA estimates screen luminance with a plain 2.4 power curve
+    let luminance = |c: Rgb<u8, Srgb>| {
+        let y = 0.2126729 * (c.r as f64 / 255.).powf(MAIN_TRC)
+            + 0.7151522 * (c.g as f64 / 255.).powf(MAIN_TRC)
+            + 0.0721750 * (c.b as f64 / 255.).powf(MAIN_TRC);
+        // Soft clamp near black to model flare
+        if y > BLK_THRS { y } else { y + (BLK_THRS - y).powf(BLK_CLMP) }
+    };
+    let (y_txt, y_bg) = (luminance(text), luminance(bg));
+    if (y_bg - y_txt).abs() < DELTA_Y_MIN {
+        return 0.;
+    }
+    let lc = if y_bg > y_txt {
+        let sapc = (y_bg.powf(NORM_BG) - y_txt.powf(NORM_TXT)) * SCALE;
+        if sapc < LO_CLIP { 0. } else { sapc - LO_OFFSET }
+    }else{
+        let sapc = (y_bg.powf(REV_BG) - y_txt.powf(REV_TXT)) * SCALE;
+        if sapc > -LO_CLIP { 0. } else { sapc + LO_OFFSET }
+    };
+    (lc * 100.) as f32
+}
+
+/// Scales a linear buffer in place by `2^stops`.
+pub fn apply_exposure(buf: &mut [Rgb<f32, LinearRgb>], stops: f32) {
+    for c in buf.iter_mut() {
+        *c = c.apply_exposure(stops);
+    }
+}
+
+/// Raises every channel of a linear buffer to `gamma` in place.
+pub fn apply_gamma(buf: &mut [Rgb<f32, LinearRgb>], gamma: f32) {
+    for c in buf.iter_mut() {
+        *c = c.apply_gamma(gamma);
+    }
+}
+
 impl<T: Channel, S> Color<T> for Rgb<T, S> {
     /// Clamps the components of the color to the range `(lo,hi)`.
     #[inline]
@@ -313,6 +1023,7 @@ impl<T:Channel, S: TransferFunction> ToHsv for Rgb<T, S> {
             * cast(60u8);
 
             let s = chr / mx;
+            let h = cast(normalize_degrees(cast::<U, f64>(h)));
 
             Hsv::new(Deg(h), s, mx)
 
@@ -323,14 +1034,27 @@ impl<T:Channel, S: TransferFunction> ToHsv for Rgb<T, S> {
 }
 
 impl<T: Channel, S: MatrixColorSpace + TransferFunction> ToXyz for Rgb<T, S> {
-    type WhitePoint = D65;
-    fn to_xyz<U: Channel + Float>(&self) -> Xyz<U, D65> {
+    type WhitePoint = S::WhitePoint;
+    fn to_xyz<U: Channel + Float>(&self) -> Xyz<U, S::WhitePoint> {
         let rgb = self.to_rgb().to_linear();
         let xyz = S::to_xyz_matrix() * rgb.into();
         Xyz::new(xyz[0], xyz[1], xyz[2])
     }
 }
 
+impl<T: Channel, S: MatrixColorSpace + TransferFunction> Rgb<T, S> {
+    /// Converts from XYZ relative to the white point of `S` into the RGB
+    /// space `S`, the counterpart of `to_xyz` for spaces other than sRGB.
+    pub fn from_xyz<U: Channel + Float>(xyz: &Xyz<U, S::WhitePoint>) -> Rgb<T, S> {
+        let rgb = S::to_rgb_matrix() * Vec3([xyz.x, xyz.y, xyz.z]);
+        Rgb::new(
+            S::from_linear(rgb[0]).to_channel(),
+            S::from_linear(rgb[1]).to_channel(),
+            S::from_linear(rgb[2]).to_channel(),
+        )
+    }
+}
+
 impl<T: Channel, S> Mul for Rgb<T, S> {
     type Output = Rgb<T, S>;
 
@@ -452,7 +1176,9 @@ impl<T, S> BorrowMut<[T;3]> for Rgb<T, S> {
 /// SVG 1.0 color constants: http://www.w3.org/TR/SVG/types.html#ColorKeywords
 pub mod consts {
     use Rgb;
-    use color_space::Srgb;
+    use color_space::{Srgb, D65};
+    use lab::{Lab, ToLab};
+    use xyz::ToXyz;
 
     pub static ALICEBLUE:               Rgb<u8, Srgb> = Rgb::new(0xF0, 0xF8, 0xFF);
     pub static ANTIQUEWHITE:            Rgb<u8, Srgb> = Rgb::new(0xFA, 0xEB, 0xD7);
@@ -593,13 +1319,176 @@ pub mod consts {
     pub static WHITESMOKE:              Rgb<u8, Srgb> = Rgb::new(0xF5, 0xF5, 0xF5);
     pub static YELLOW:                  Rgb<u8, Srgb> = Rgb::new(0xFF, 0xFF, 0x00);
     pub static YELLOWGREEN:             Rgb<u8, Srgb> = Rgb::new(0x9A, 0xCD, 0x32);
+
+    /// Every named color above with its lowercase CSS name, in alphabetical
+    /// order.
+    pub static ALL: [(&str, Rgb<u8, Srgb>); 139] = [
+        ("aliceblue", ALICEBLUE),
+        ("antiquewhite", ANTIQUEWHITE),
+        ("aqua", AQUA),
+        ("aquamarine", AQUAMARINE),
+        ("azure", AZURE),
+        ("beige", BEIGE),
+        ("bisque", BISQUE),
+        ("black", BLACK),
+        ("blanchedalmond", BLANCHEDALMOND),
+        ("blue", BLUE),
+        ("blueviolet", BLUEVIOLET),
+        ("brown", BROWN),
+        ("burlywood", BURLYWOOD),
+        ("cadetblue", CADETBLUE),
+        ("chartreuse", CHARTREUSE),
+        ("chocolate", CHOCOLATE),
+        ("coral", CORAL),
+        ("cornflowerblue", CORNFLOWERBLUE),
+        ("cornsilk", CORNSILK),
+        ("crimson", CRIMSON),
+        ("cyan", CYAN),
+        ("darkblue", DARKBLUE),
+        ("darkcyan", DARKCYAN),
+        ("darkgoldenrod", DARKGOLDENROD),
+        ("darkgray", DARKGRAY),
+        ("darkgreen", DARKGREEN),
+        ("darkkhaki", DARKKHAKI),
+        ("darkmagenta", DARKMAGENTA),
+        ("darkolivegreen", DARKOLIVEGREEN),
+        ("darkorange", DARKORANGE),
+        ("darkorchid", DARKORCHID),
+        ("darkred", DARKRED),
+        ("darksalmon", DARKSALMON),
+        ("darkseagreen", DARKSEAGREEN),
+        ("darkslateblue", DARKSLATEBLUE),
+        ("darkslategray", DARKSLATEGRAY),
+        ("darkturquoise", DARKTURQUOISE),
+        ("darkviolet", DARKVIOLET),
+        ("deeppink", DEEPPINK),
+        ("deepskyblue", DEEPSKYBLUE),
+        ("dimgray", DIMGRAY),
+        ("dodgerblue", DODGERBLUE),
+        ("firebrick", FIREBRICK),
+        ("floralwhite", FLORALWHITE),
+        ("forestgreen", FORESTGREEN),
+        ("fuchsia", FUCHSIA),
+        ("gainsboro", GAINSBORO),
+        ("ghostwhite", GHOSTWHITE),
+        ("gold", GOLD),
+        ("goldenrod", GOLDENROD),
+        ("gray", GRAY),
+        ("green", GREEN),
+        ("greenyellow", GREENYELLOW),
+        ("honeydew", HONEYDEW),
+        ("hotpink", HOTPINK),
+        ("indianred", INDIANRED),
+        ("indigo", INDIGO),
+        ("ivory", IVORY),
+        ("khaki", KHAKI),
+        ("lavender", LAVENDER),
+        ("lavenderblush", LAVENDERBLUSH),
+        ("lawngreen", LAWNGREEN),
+        ("lemonchiffon", LEMONCHIFFON),
+        ("lightblue", LIGHTBLUE),
+        ("lightcoral", LIGHTCORAL),
+        ("lightcyan", LIGHTCYAN),
+        ("lightgoldenrodyellow", LIGHTGOLDENRODYELLOW),
+        ("lightgreen", LIGHTGREEN),
+        ("lightgrey", LIGHTGREY),
+        ("lightpink", LIGHTPINK),
+        ("lightsalmon", LIGHTSALMON),
+        ("lightseagreen", LIGHTSEAGREEN),
+        ("lightskyblue", LIGHTSKYBLUE),
+        ("lightslategray", LIGHTSLATEGRAY),
+        ("lightsteelblue", LIGHTSTEELBLUE),
+        ("lightyellow", LIGHTYELLOW),
+        ("lime", LIME),
+        ("limegreen", LIMEGREEN),
+        ("linen", LINEN),
+        ("magenta", MAGENTA),
+        ("maroon", MAROON),
+        ("mediumaquamarine", MEDIUMAQUAMARINE),
+        ("mediumblue", MEDIUMBLUE),
+        ("mediumorchid", MEDIUMORCHID),
+        ("mediumpurple", MEDIUMPURPLE),
+        ("mediumseagreen", MEDIUMSEAGREEN),
+        ("mediumslateblue", MEDIUMSLATEBLUE),
+        ("mediumspringgreen", MEDIUMSPRINGGREEN),
+        ("mediumturquoise", MEDIUMTURQUOISE),
+        ("mediumvioletred", MEDIUMVIOLETRED),
+        ("midnightblue", MIDNIGHTBLUE),
+        ("mintcream", MINTCREAM),
+        ("mistyrose", MISTYROSE),
+        ("moccasin", MOCCASIN),
+        ("navajowhite", NAVAJOWHITE),
+        ("navy", NAVY),
+        ("oldlace", OLDLACE),
+        ("olive", OLIVE),
+        ("olivedrab", OLIVEDRAB),
+        ("orange", ORANGE),
+        ("orangered", ORANGERED),
+        ("orchid", ORCHID),
+        ("palegoldenrod", PALEGOLDENROD),
+        ("palegreen", PALEGREEN),
+        ("palevioletred", PALEVIOLETRED),
+        ("papayawhip", PAPAYAWHIP),
+        ("peachpuff", PEACHPUFF),
+        ("peru", PERU),
+        ("pink", PINK),
+        ("plum", PLUM),
+        ("powderblue", POWDERBLUE),
+        ("purple", PURPLE),
+        ("red", RED),
+        ("rosybrown", ROSYBROWN),
+        ("royalblue", ROYALBLUE),
+        ("saddlebrown", SADDLEBROWN),
+        ("salmon", SALMON),
+        ("sandybrown", SANDYBROWN),
+        ("seagreen", SEAGREEN),
+        ("seashell", SEASHELL),
+        ("sienna", SIENNA),
+        ("silver", SILVER),
+        ("skyblue", SKYBLUE),
+        ("slateblue", SLATEBLUE),
+        ("slategray", SLATEGRAY),
+        ("snow", SNOW),
+        ("springgreen", SPRINGGREEN),
+        ("steelblue", STEELBLUE),
+        ("tan", TAN),
+        ("teal", TEAL),
+        ("thistle", THISTLE),
+        ("tomato", TOMATO),
+        ("turquoise", TURQUOISE),
+        ("violet", VIOLET),
+        ("wheat", WHEAT),
+        ("white", WHITE),
+        ("whitesmoke", WHITESMOKE),
+        ("yellow", YELLOW),
+        ("yellowgreen", YELLOWGREEN),
+    ];
+
+    /// Named color by its CSS name, ignoring case.
+    pub fn by_name(name: &str) -> Option<Rgb<u8, Srgb>> {
+        ALL.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, c)| *c)
+    }
+
+    /// Name of the named color closest to `color` by CIEDE2000.
+    pub fn nearest_name(color: Rgb<u8, Srgb>) -> &'static str {
+        let lab = |c: &Rgb<u8, Srgb>| -> Lab<f32, D65> { c.to_xyz::<f32>().to_lab() };
+        let target = lab(&color);
+        ALL.iter()
+            .map(|(name, c)| (*name, target.delta_e_2000(&lab(c))))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(name, _)| name)
+            .unwrap()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use {Hsv, ToHsv};
     use {Rgb, ToRgb};
-    use FloatColor;
+    use super::{deinterleave, interleave, apply_exposure, apply_gamma, apca_contrast, contrast_ratio, Temperature, ColorCategory};
+    use super::{srgb_slice_to_linear, srgb_slice_to_linear_into, linear_slice_to_srgb, linear_slice_to_srgb_into};
+    use color_space::{LinearRgb, Srgb, TransferFunction};
+    use {FloatColor, RoundingMode};
     use angle::*;
     use num_traits::Saturating;
 
@@ -609,12 +1498,565 @@ mod tests {
         assert_eq!(Rgb::<u8>::new(0xA0, 0xA0, 0xA0).to_rgb::<u16>(), Rgb::<u16>::new(0xA0A0, 0xA0A0, 0xA0A0));
     }
 
+    #[test]
+    fn test_rgb_u8_f32_round_trip() {
+        for c in 0..=u8::MAX {
+            let rgb = Rgb::<u8>::new(c, c.wrapping_add(85), c.wrapping_add(170));
+            assert_eq!(rgb.to_rgb::<f32>().to_rgb::<u8>(), rgb);
+        }
+    }
+
+    #[test]
+    fn test_deinterleave_interleave() {
+        let src = [rgb!(0.1f32, 0.2, 0.3), rgb!(0.4, 0.5, 0.6), rgb!(0.7, 0.8, 0.9)];
+        let mut r = [0.; 3];
+        let mut g = [0.; 3];
+        let mut b = [0.; 3];
+        deinterleave(&src, &mut r, &mut g, &mut b);
+        assert_eq!(r, [0.1, 0.4, 0.7]);
+        assert_eq!(g, [0.2, 0.5, 0.8]);
+        assert_eq!(b, [0.3, 0.6, 0.9]);
+
+        let mut dst = [rgb!(0.0f32); 3];
+        interleave(&r, &g, &b, &mut dst);
+        assert_eq!(dst, src);
+    }
+
+    #[test]
+    fn test_rgbx() {
+        assert_eq!(rgb!(1u8, 2, 3).to_rgbx(), [1, 2, 3, 0xFF]);
+        assert_eq!(Rgb::<u8>::from_rgbx([1, 2, 3, 0x10]), rgb!(1, 2, 3));
+    }
+
+    #[test]
+    fn test_to_gray_levels() {
+        assert_eq!(rgb!(0xFFu8, 0xFF, 0x00).to_gray_levels(2), rgb!(0xFF));
+        assert_eq!(rgb!(0x00u8, 0x00, 0xFF).to_gray_levels(2), rgb!(0x00));
+        assert_eq!(rgb!(0x00u8, 0xFF, 0x00).to_gray_levels(2), rgb!(0xFF));
+        assert_eq!(rgb!(0xFFu8, 0x00, 0x00).to_gray_levels(2), rgb!(0x00));
+        assert_eq!(rgb!(0x80u8).to_gray_levels(2), rgb!(0xFF));
+        assert_eq!(rgb!(0x7Fu8).to_gray_levels(2), rgb!(0x00));
+
+        assert_eq!(rgb!(0x80u8).to_gray_levels(3), rgb!(0x80));
+        assert_eq!(rgb!(0x50u8).to_gray_levels(5), rgb!(0x40));
+        let mut levels = (0..=255u8).map(|c| rgb!(c).to_gray_levels(16).r).collect::<Vec<_>>();
+        levels.dedup();
+        assert_eq!(levels.len(), 16);
+    }
+
+    #[test]
+    fn test_within() {
+        let c = rgb!(100u8, 0, 255);
+        assert!(c.within(&c, 0));
+        assert!(c.within(&rgb!(102, 2, 253), 2));
+        assert!(!c.within(&rgb!(103, 0, 255), 2));
+        assert!(!c.within(&rgb!(100, 0, 252), 2));
+        assert!(rgb!(0u8, 0, 0).within(&rgb!(255, 255, 255), 255));
+    }
+
     #[test]
     fn test_rgb_to_hsv() {
         assert_eq!(Rgb::<u8>::new(0xFF, 0xFF, 0xFF).to_hsv::<f32>(), Hsv::<f32>::new(Deg(0.0), 0.0, 1.0));
         assert_eq!(Rgb::<u8>::new(0x99, 0x00, 0x00).to_hsv::<f32>(), Hsv::<f32>::new(Deg(0.0), 1.0, 0.6));
         assert_eq!(Rgb::<u8>::new(0x00, 0x99, 0x00).to_hsv::<f32>(), Hsv::<f32>::new(Deg(120.0), 1.0, 0.6));
         assert_eq!(Rgb::<u8>::new(0x00, 0x00, 0x99).to_hsv::<f32>(), Hsv::<f32>::new(Deg(240.0), 1.0, 0.6));
+        assert_eq!(Rgb::<u8>::new(0xFF, 0x00, 0x80).to_hsv::<f32>().h.value().round(), 330.);
+    }
+
+    #[test]
+    fn test_decode_encode_srgb() {
+        let colors = [
+            rgb!(0.0f32, 0.0, 0.0),
+            rgb!(1.0f32, 1.0, 1.0),
+            rgb!(0.5f32, 0.25, 0.75),
+            rgb!(0.9f32, 0.1, 0.3),
+        ];
+        for c in colors.iter() {
+            let linear = c.decode();
+            let encoded = linear.encode_srgb();
+            assert!((encoded.r - c.r).abs() < 1e-5, "{:?} {:?}", c, encoded);
+            assert!((encoded.g - c.g).abs() < 1e-5, "{:?} {:?}", c, encoded);
+            assert!((encoded.b - c.b).abs() < 1e-5, "{:?} {:?}", c, encoded);
+        }
+        assert!((rgb!(0.5f32, 0.5, 0.5).decode().r - 0.21404).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_chroma() {
+        assert_eq!(rgb!(0xFFu8, 0, 0).chroma(), 0xFF);
+        assert_eq!(rgb!(0u8, 0, 0xFF).chroma(), 0xFF);
+        assert_eq!(rgb!(0.0f32, 1.0, 0.0).chroma(), 1.);
+        assert_eq!(rgb!(0x80u8, 0x80, 0x80).chroma(), 0);
+        assert_eq!(rgb!(0.3f32, 0.3, 0.3).chroma(), 0.);
+        let c = rgb!(0x20u8, 0xC0, 0x60);
+        assert_eq!(c.max_channel(), 0xC0);
+        assert_eq!(c.min_channel(), 0x20);
+        assert_eq!(c.chroma(), 0xA0);
+    }
+
+    #[test]
+    fn test_linear_to_xyz() {
+        use xyz::ToXyz;
+        for c in [Rgb::<f32, LinearRgb>::new(0.2, 0.5, 0.9), Rgb::new(1., 0., 0.), Rgb::new(0.01, 0.01, 0.01)].iter() {
+            let xyz = c.to_xyz::<f32>();
+            let expected = c.encode_srgb().to_xyz::<f32>();
+            assert!((xyz.x - expected.x).abs() < 1e-5, "{:?} {:?}", xyz, expected);
+            assert!((xyz.y - expected.y).abs() < 1e-5, "{:?} {:?}", xyz, expected);
+            assert!((xyz.z - expected.z).abs() < 1e-5, "{:?} {:?}", xyz, expected);
+        }
+    }
+
+    #[test]
+    fn test_from_hex_str() {
+        use error::ParseError;
+        let white = Rgb::<u8>::new(0xFF, 0xFF, 0xFF);
+        assert_eq!(Rgb::from_hex_str("#fff"), Ok(white));
+        assert_eq!(Rgb::from_hex_str("#FFFFFF"), Ok(white));
+        assert_eq!(Rgb::from_hex_str("fFfFfF"), Ok(white));
+        assert_eq!(Rgb::<u8>::from_hex_str("#0a5"), Ok(Rgb::new(0x00, 0xAA, 0x55)));
+        assert_eq!(Rgb::<u8>::from_hex_str("12aB9f"), Ok(Rgb::new(0x12, 0xAB, 0x9F)));
+        assert_eq!(Rgb::<u8>::from_hex_str("#12345"), Err(ParseError::InvalidLength(5)));
+        assert_eq!(Rgb::<u8>::from_hex_str(""), Err(ParseError::InvalidLength(0)));
+        assert_eq!(Rgb::<u8>::from_hex_str("#12g456"), Err(ParseError::InvalidDigit('g')));
+        assert_eq!(Rgb::<u8>::from_hex_str("##123456"), Err(ParseError::InvalidDigit('#')));
+    }
+
+    #[test]
+    fn test_from_str() {
+        use error::ParseError;
+        use super::consts;
+        assert_eq!("red".parse(), Ok(consts::RED));
+        assert_eq!("AliceBlue".parse(), Ok(consts::ALICEBLUE));
+        assert_eq!("#ff0000".parse(), Ok(consts::RED));
+        assert_eq!("00f".parse(), Ok(consts::BLUE));
+        assert_eq!("reddish".parse::<Rgb<u8>>(), Err(ParseError::UnknownName));
+        assert_eq!("#ff00".parse::<Rgb<u8>>(), Err(ParseError::InvalidLength(4)));
+        assert_eq!(consts::by_name("DARKSLATEGRAY"), Some(consts::DARKSLATEGRAY));
+        assert_eq!(consts::by_name("nope"), None);
+    }
+
+    #[test]
+    fn test_nearest_name() {
+        use super::consts;
+        assert_eq!(consts::nearest_name(Rgb::new(0xFE, 0x01, 0x00)), "red");
+        assert_eq!(consts::nearest_name(Rgb::new(0x01, 0x02, 0x03)), "black");
+        assert_eq!(consts::nearest_name(Rgb::new(0x64, 0x95, 0xEC)), "cornflowerblue");
+        for (name, c) in consts::ALL.iter() {
+            let nearest = consts::nearest_name(*c);
+            // aqua/cyan and fuchsia/magenta share their color
+            assert_eq!(consts::by_name(nearest), Some(*c), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_to_hex_string() {
+        let c = Rgb::<u8>::new(0, 5, 255);
+        assert_eq!(c.to_hex_string(), "#0005ff");
+        assert_eq!(c.to_hex_string_upper(), "#0005FF");
+        for (name, c) in super::consts::ALL.iter() {
+            assert_eq!(Rgb::from_hex_str(&c.to_hex_string()), Ok(*c), "{}", name);
+            assert_eq!(Rgb::from_hex_str(&c.to_hex_string_upper()), Ok(*c), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_clamp_to() {
+        let min = Rgb::<u8>::new(10, 100, 0);
+        let max = Rgb::<u8>::new(50, 200, 30);
+        assert_eq!(Rgb::<u8>::new(0, 255, 20).clamp_to(&min, &max), Rgb::new(10, 200, 20));
+        assert_eq!(Rgb::<u8>::new(60, 50, 30).clamp_to(&min, &max), Rgb::new(50, 100, 30));
+        let min = Rgb::<f32>::new(0.2, 0., 0.5);
+        let max = Rgb::<f32>::new(0.4, 0.1, 1.);
+        assert_eq!(Rgb::<f32>::new(0.5, 0.05, 0.2).clamp_to(&min, &max), Rgb::new(0.4, 0.05, 0.5));
+    }
+
+    #[test]
+    fn test_debug_normalized() {
+        assert_eq!(Rgb::<u8>::new(255, 128, 0).debug_normalized(), "Rgb(1.000, 0.502, 0.000)");
+        assert_eq!(Rgb::<f32>::new(1., 0.502, 0.).debug_normalized(), "Rgb(1.000, 0.502, 0.000)");
+        assert_eq!(Rgb::<u16>::new(0, 32768, 65535).debug_normalized(), "Rgb(0.000, 0.500, 1.000)");
+        assert_eq!(Rgb::<f32, LinearRgb>::new(1.5, -0.25, 0.).debug_normalized(), "Rgb(1.500, -0.250, 0.000)");
+    }
+
+    #[test]
+    fn test_to_u8_with() {
+        let c = rgb!(0.5 / 255., 1.5 / 255., 127.5 / 255.);
+        assert_eq!(c.to_u8_with(RoundingMode::HalfUp), rgb!(1, 2, 128));
+        assert_eq!(c.to_u8_with(RoundingMode::HalfEven), rgb!(0, 2, 128));
+        assert_eq!(c.to_u8_with(RoundingMode::Truncate), rgb!(0, 1, 127));
+        assert_eq!(c.to_u8_with(RoundingMode::default()), c.to_rgb::<u8>());
+
+        let c = rgb!(0.4 / 255., 2.6 / 255., 2.);
+        assert_eq!(c.to_u8_with(RoundingMode::HalfUp), rgb!(0, 3, 255));
+        assert_eq!(c.to_u8_with(RoundingMode::HalfEven), rgb!(0, 3, 255));
+        assert_eq!(c.to_u8_with(RoundingMode::Truncate), rgb!(0, 2, 255));
+    }
+
+    #[test]
+    fn test_temperature() {
+        assert_eq!(rgb!(0xFF, 0x8C, 0x00).temperature(), Temperature::Warm);
+        assert!(rgb!(0xFF, 0x8C, 0x00).is_warm());
+        assert!(rgb!(0xE0, 0x10, 0x20).is_warm());
+        assert!(rgb!(0xF0, 0xE0, 0x30).is_warm());
+        assert_eq!(rgb!(0x20, 0x40, 0xFF).temperature(), Temperature::Cool);
+        assert!(rgb!(0x20, 0x40, 0xFF).is_cool());
+        assert!(rgb!(0x20, 0xA0, 0x40).is_cool());
+        assert!(rgb!(0x80, 0x20, 0xC0).is_cool());
+        assert_eq!(rgb!(0x80, 0x80, 0x80).temperature(), Temperature::Neutral);
+        assert_eq!(rgb!(0x84, 0x80, 0x7C).temperature(), Temperature::Neutral);
+        assert!(!rgb!(0x80, 0x80, 0x80).is_warm() && !rgb!(0x80, 0x80, 0x80).is_cool());
+    }
+
+    #[test]
+    fn test_gray_axis_distance() {
+        for &v in [0u8, 0x40, 0x80, 0xFF].iter() {
+            let gray = rgb!(v, v, v);
+            assert!(gray.gray_axis_distance() < 0.01, "{:?} {}", gray, gray.gray_axis_distance());
+            assert!(gray.is_neutral(1.));
+        }
+        assert!(rgb!(0xFF, 0, 0).gray_axis_distance() > 100.);
+        assert!(!rgb!(0xFF, 0, 0).is_neutral(2.));
+        assert!(rgb!(0x80, 0x80, 0x82).is_neutral(2.));
+    }
+
+    #[test]
+    fn test_from_hsv_hsl() {
+        assert_eq!(Rgb::from_hsl(0., 1., 0.5), rgb!(0xFF, 0, 0));
+        assert_eq!(Rgb::from_hsv(120., 1., 1.), rgb!(0, 0xFF, 0));
+        assert_eq!(Rgb::from_hsv(-120., 1., 1.), rgb!(0, 0, 0xFF));
+        assert_eq!(Rgb::from_hsv(600., 2., 1.), rgb!(0, 0, 0xFF));
+        assert_eq!(Rgb::from_hsl(210., 0.5, 0.25), rgb!(0x20, 0x40, 0x60));
+        assert_eq!(Rgb::from_hsl(0., 0., 1.), rgb!(0xFF, 0xFF, 0xFF));
+        assert_eq!(Rgb::from_hsl(0., 1., 0.), rgb!(0, 0, 0));
+    }
+
+    #[test]
+    fn test_apply_exposure_gamma() {
+        let colors = [
+            Rgb::<f32, LinearRgb>::new(0.1, 0.25, 0.5),
+            Rgb::new(0., 1., 0.02),
+            Rgb::new(0.8, -0.1, 2.),
+        ];
+        let mut buf = colors;
+        apply_exposure(&mut buf, 1.);
+        for (c, e) in colors.iter().zip(buf.iter()) {
+            assert_eq!((e.r, e.g, e.b), (c.r * 2., c.g * 2., c.b * 2.));
+        }
+        apply_exposure(&mut buf, -1.);
+        assert_eq!(buf, colors);
+
+        apply_gamma(&mut buf, 2.2);
+        for (c, g) in colors.iter().zip(buf.iter()) {
+            assert_eq!(*g, c.apply_gamma(2.2));
+        }
+        assert!((buf[0].b - 0.5f32.powf(2.2)).abs() < 1e-7);
+        assert!((buf[2].g + 0.1f32.powf(2.2)).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_white_balance() {
+        let gray_card = rgb!(0x9A, 0x80, 0x6A);
+        let balanced = gray_card.white_balance(gray_card);
+        assert!(balanced.chroma() <= 1, "{:?}", balanced);
+        assert!(balanced.r > 0x70 && balanced.r < 0x90, "{:?}", balanced);
+
+        // A warm cast turns a neutral color blue
+        let gray = rgb!(0x80, 0x80, 0x80).white_balance(gray_card);
+        assert!(gray.b > gray.g && gray.g > gray.r, "{:?}", gray);
+        let neutral = rgb!(0x80, 0x80, 0x80);
+        assert_eq!(rgb!(0x12, 0x34, 0x56).white_balance(neutral), rgb!(0x12, 0x34, 0x56));
+    }
+
+    #[test]
+    fn test_to_ansi_16() {
+        assert_eq!(rgb!(0xCD, 0, 0).to_ansi_16(), 1);
+        assert_eq!(rgb!(0xB0, 0x10, 0x10).to_ansi_16(), 1);
+        assert_eq!(rgb!(0xFF, 0, 0).to_ansi_16(), 9);
+        assert_eq!(rgb!(0xFF, 0xFF, 0xFF).to_ansi_16(), 15);
+        assert_eq!(rgb!(0xFA, 0xFA, 0xFA).to_ansi_16(), 15);
+        assert_eq!(rgb!(0, 0, 0).to_ansi_16(), 0);
+        assert_eq!(rgb!(0x80, 0x80, 0x80).to_ansi_16(), 8);
+        assert_eq!(rgb!(0x10, 0x20, 0xE0).to_ansi_16(), 4);
+    }
+
+    #[test]
+    fn test_linear_u16() {
+        assert_eq!(Rgb::<u8>::new(0, 0, 0).to_linear_u16(), Rgb::new(0, 0, 0));
+        assert_eq!(Rgb::<u8>::new(255, 255, 255).to_linear_u16(), Rgb::new(0xFFFF, 0xFFFF, 0xFFFF));
+        assert_eq!(Rgb::from_linear_u16(Rgb::new(0, 0, 0)), Rgb::<u8>::new(0, 0, 0));
+        assert_eq!(Rgb::from_linear_u16(Rgb::new(0xFFFF, 0xFFFF, 0xFFFF)), Rgb::<u8>::new(255, 255, 255));
+
+        let mut last = None;
+        for v in 0..=255u8 {
+            let linear = Rgb::<u8>::new(v, v, v).to_linear_u16();
+            assert!(last.is_none_or(|l| linear.r > l), "{} {:?}", v, linear);
+            last = Some(linear.r);
+            assert_eq!(Rgb::from_linear_u16(linear), Rgb::<u8>::new(v, v, v));
+        }
+        let mut last = 0;
+        for v in 0..=0xFFFFu16 {
+            let c = Rgb::from_linear_u16(Rgb::new(v, v, v));
+            assert!(c.r >= last, "{} {:?}", v, c);
+            last = c.r;
+        }
+    }
+
+    #[test]
+    fn test_to_linear_f32_threads() {
+        use std::sync::{Arc, Barrier};
+        use std::thread;
+
+        let threads = 8;
+        let barrier = Arc::new(Barrier::new(threads));
+        let handles: Vec<_> = (0..threads).map(|_| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                // Release all threads together into the first initialization
+                barrier.wait();
+                (0..=255u8).map(|v| rgb!(v, v, v).to_linear_f32().r).collect::<Vec<f32>>()
+            })
+        }).collect();
+        let expected: Vec<f32> = (0..=255u8).map(|v| rgb!(v, v, v).to_rgb::<f32>().to_linear().r).collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_splat() {
+        assert_eq!(Rgb::<u8>::splat(0x80), rgb!(0x80, 0x80, 0x80));
+        assert_eq!(Rgb::<f32, LinearRgb>::splat(0.5), Rgb::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn test_to_web_safe() {
+        assert_eq!(rgb!(0x40, 0x40, 0x40).to_web_safe(), rgb!(0x33, 0x33, 0x33));
+        assert_eq!(rgb!(0x00, 0x19, 0x1A).to_web_safe(), rgb!(0x00, 0x00, 0x33));
+        assert_eq!(rgb!(0xFF, 0xE6, 0xE5).to_web_safe(), rgb!(0xFF, 0xFF, 0xCC));
+        for i in 0..=255u8 {
+            let c = rgb!(i, i.wrapping_mul(7), i.wrapping_mul(13));
+            for safe in [c.to_web_safe(), c.to_web_safe_perceptual()].iter() {
+                assert!(safe.r % 51 == 0 && safe.g % 51 == 0 && safe.b % 51 == 0, "{:?} {:?}", c, safe);
+            }
+        }
+        assert_eq!(rgb!(0x66, 0xCC, 0xFF).to_web_safe_perceptual(), rgb!(0x66, 0xCC, 0xFF));
+    }
+
+    #[test]
+    fn test_mix_subtractive() {
+        let blue = rgb!(0, 0, 0xFF);
+        let yellow = rgb!(0xFF, 0xFF, 0);
+        let green = blue.mix_subtractive(&yellow, 0.5);
+        assert!(green.g > green.r + 0x40 && green.g > green.b + 0x40, "{:?}", green);
+        assert_eq!(blue.mix_subtractive(&yellow, 0.), blue);
+        assert_eq!(blue.mix_subtractive(&yellow, 1.), yellow);
+
+        let red = rgb!(0xFF, 0, 0);
+        let orange = red.mix_subtractive(&yellow, 0.5);
+        assert!(orange.r > orange.g && orange.g > orange.b, "{:?}", orange);
+        let gray = rgb!(0x80, 0x80, 0x80);
+        assert_eq!(gray.mix_subtractive(&gray, 0.3), gray);
+    }
+
+    #[test]
+    fn test_wcag() {
+        let black = rgb!(0, 0, 0);
+        let white = rgb!(0xFF, 0xFF, 0xFF);
+        assert!((black.contrast_against(&white) - 21.).abs() < 1e-4);
+        assert!((white.contrast_against(&black) - 21.).abs() < 1e-4);
+        assert!((white.contrast_against(&white) - 1.).abs() < 1e-6);
+
+        // #767676 is the lightest gray passing AA on white, ~4.54:1
+        let gray = rgb!(0x76, 0x76, 0x76);
+        assert!(gray.meets_wcag_aa(&white, false));
+        assert!(gray.meets_wcag_aa(&white, true));
+        assert!(!gray.meets_wcag_aaa(&white, false));
+        assert!(gray.meets_wcag_aaa(&white, true));
+
+        // #949494 is ~3.03:1 on white
+        let light = rgb!(0x94, 0x94, 0x94);
+        assert!(!light.meets_wcag_aa(&white, false));
+        assert!(light.meets_wcag_aa(&white, true));
+        assert!(!light.meets_wcag_aaa(&white, true));
+        assert!(!rgb!(0xAA, 0xAA, 0xAA).meets_wcag_aa(&white, true));
+        assert!(black.meets_wcag_aaa(&white, false));
+    }
+
+    #[test]
+    fn test_luminance_weighted_mean() {
+        use xyz::ToXyz;
+        let luminance = |c: Rgb<u8>| c.to_xyz::<f64>().y;
+        let red = Rgb::<u8>::new(0xFF, 0, 0);
+        let blue = Rgb::<u8>::new(0x20, 0x40, 0xFF);
+        for &(wr, wb) in [(1f32, 1f32), (2., 1.), (1., 3.)].iter() {
+            let mean = Rgb::luminance_weighted_mean(&[(red, wr), (blue, wb)]);
+            let expected = (luminance(red) * wr as f64 + luminance(blue) * wb as f64) / (wr + wb) as f64;
+            assert!((luminance(mean) - expected).abs() < 0.005, "{:?} {} {}", mean, luminance(mean), expected);
+        }
+
+        let equal = Rgb::luminance_weighted_mean(&[(red, 1.), (blue, 1.)]);
+        let doubled = Rgb::luminance_weighted_mean(&[(red, 2.), (blue, 1.)]);
+        assert!(doubled.r > equal.r && doubled.b < equal.b, "{:?} {:?}", equal, doubled);
+        assert_eq!(Rgb::luminance_weighted_mean(&[(red, 1.)]), red);
+        assert_eq!(Rgb::luminance_weighted_mean(&[]), Rgb::new(0, 0, 0));
+    }
+
+    #[test]
+    fn test_adapt_display() {
+        use color_space::{D50, D65};
+        for v in [32u8, 128, 200] {
+            let gray = Rgb::<u8>::new(v, v, v);
+            let d50 = gray.adapt_display(D65, D50);
+            let back = d50.adapt_display(D50, D65);
+            for (a, b) in [(back.r, v), (back.g, v), (back.b, v)] {
+                assert!((a as i32 - b as i32).abs() <= 1, "{:?} -> {:?} -> {:?}", gray, d50, back);
+            }
+        }
+
+        // D50 is warmer than D65
+        let white = Rgb::<u8>::new(255, 255, 255).adapt_display(D65, D50);
+        assert!(white.r > white.b, "{:?}", white);
+    }
+
+    #[test]
+    fn test_to_linear_extended_range() {
+        let c = rgb!(1.2f32, 0.5, -0.1);
+        let unclamped = c.to_linear();
+        assert!((unclamped.r - 1.5168).abs() < 1e-4, "{:?}", unclamped);
+        assert!(unclamped.b < 0., "{:?}", unclamped);
+        let clamped = c.to_linear_clamped();
+        assert_eq!(clamped.r, 1.);
+        assert_eq!(clamped.g, unclamped.g);
+        assert_eq!(clamped.b, 0.);
+    }
+
+    #[test]
+    fn test_mix_as_light() {
+        let yellow = rgb!(0xFFu8, 0, 0).mix_as_light(&rgb!(0, 0xFF, 0));
+        assert!(yellow.r > 0xF0 && yellow.g > 0xF0 && yellow.b < 0x10, "{:?}", yellow);
+        let red = rgb!(0xFFu8, 0, 0).mix_as_light(&rgb!(0xFF, 0, 0));
+        assert!(red.r > 0xF0 && red.g < 0x10 && red.b < 0x10, "{:?}", red);
+        let dark_red = rgb!(0x80u8, 0, 0).mix_as_light(&rgb!(0, 0, 0));
+        assert!((dark_red.r as i32 - 0x80).abs() <= 1 && dark_red.g == 0 && dark_red.b == 0, "{:?}", dark_red);
+    }
+
+    #[cfg(feature="rand")]
+    #[test]
+    fn test_random_is_deterministic() {
+        use rand::{SeedableRng, rngs::StdRng};
+        let mut rng1 = StdRng::seed_from_u64(0);
+        let mut rng2 = StdRng::seed_from_u64(0);
+        for _ in 0..16 {
+            assert_eq!(Rgb::random(&mut rng1), Rgb::random(&mut rng2));
+            assert_eq!(Rgb::random_hue(&mut rng1), Rgb::random_hue(&mut rng2));
+        }
+    }
+
+    #[test]
+    fn test_to_u8_dithered_averages_to_value() {
+        for &v in &[0.5f32, 0.3, 100.25 / 255.] {
+            let color = rgb!(v, v, v);
+            let mut sum = 0u32;
+            for y in 0..16 {
+                for x in 0..16 {
+                    let q = color.to_u8_dithered(x, y);
+                    assert_eq!(q.r, q.g);
+                    sum += q.r as u32;
+                }
+            }
+            let mean = sum as f32 / 256.;
+            assert!((mean - v * 255.).abs() < 1. / 16., "{} vs {}", mean, v * 255.);
+        }
+        assert_eq!(rgb!(1f32, 0., 1.).to_u8_dithered(3, 3), rgb!(255, 0, 255));
+    }
+
+    #[test]
+    fn test_linear_slice_conversions() {
+        let srgb = [rgb!(0u8, 0, 0), rgb!(0x40, 0x80, 0xC0), rgb!(0xFF, 0x12, 0x34)];
+        let linear = srgb_slice_to_linear(&srgb);
+        let mut linear_into = [rgb!(0f32, 0., 0.).to_linear(); 3];
+        srgb_slice_to_linear_into(&srgb, &mut linear_into);
+        assert_eq!(linear, linear_into);
+
+        let back = linear_slice_to_srgb(&linear);
+        let mut back_into = [rgb!(0u8, 0, 0); 3];
+        linear_slice_to_srgb_into(&linear, &mut back_into);
+        assert_eq!(back, back_into);
+        assert_eq!(back, srgb);
+    }
+
+    #[test]
+    fn test_category() {
+        let expected = [
+            (rgb!(0xFFu8, 0, 0), ColorCategory::Red),
+            (rgb!(0xC0u8, 0x10, 0x30), ColorCategory::Red),
+            (rgb!(0xFFu8, 0x80, 0), ColorCategory::Orange),
+            (rgb!(0xFFu8, 0xE0, 0x20), ColorCategory::Yellow),
+            (rgb!(0x20u8, 0xA0, 0x40), ColorCategory::Green),
+            (rgb!(0u8, 0xE0, 0xE0), ColorCategory::Cyan),
+            (rgb!(0x20u8, 0x40, 0xFF), ColorCategory::Blue),
+            (rgb!(0x80u8, 0x20, 0xE0), ColorCategory::Purple),
+            (rgb!(0xFFu8, 0x69, 0xB4), ColorCategory::Pink),
+            (rgb!(0x80u8, 0x80, 0x80), ColorCategory::Gray),
+            (rgb!(0x60u8, 0x64, 0x68), ColorCategory::Gray),
+            (rgb!(0u8, 0, 0), ColorCategory::Black),
+            (rgb!(0x20u8, 0x08, 0x10), ColorCategory::Black),
+            (rgb!(0xFFu8, 0xFF, 0xFF), ColorCategory::White),
+            (rgb!(0xF8u8, 0xF5, 0xF0), ColorCategory::White),
+        ];
+        for &(color, category) in expected.iter() {
+            assert_eq!(color.category(), category, "{:?}", color);
+        }
+    }
+
+    #[test]
+    fn test_contrast_ratio() {
+        let black = rgb!(0f32, 0., 0.);
+        let white = rgb!(1f32, 1., 1.);
+        assert_eq!(black.relative_luminance(), 0.);
+        assert!((white.relative_luminance() - 1.).abs() < 1e-6);
+        assert!((contrast_ratio(&black, &white) - 21.).abs() < 1e-4);
+        assert!((contrast_ratio(&white, &black) - 21.).abs() < 1e-4);
+        let gray = rgb!(0.5f64, 0.5, 0.5);
+        assert_eq!(contrast_ratio(&gray, &gray), 1.);
+        assert!((rgb!(0u8, 0xFF, 0).relative_luminance() as i32 - 0xB6).abs() <= 1);
+    }
+
+    #[test]
+    fn test_to_grayscale() {
+        let green = rgb!(0u8, 0xFF, 0).to_grayscale();
+        let blue = rgb!(0u8, 0, 0xFF).to_grayscale();
+        assert!(green.r == green.g && green.g == green.b, "{:?}", green);
+        assert!(blue.r == blue.g && blue.g == blue.b, "{:?}", blue);
+        assert!(green.r > blue.r, "{:?} {:?}", green, blue);
+        assert_eq!(rgb!(0x80u8, 0x80, 0x80).to_grayscale(), rgb!(0x80, 0x80, 0x80));
+        assert_eq!(rgb!(0xFFu8, 0xFF, 0xFF).to_grayscale(), rgb!(0xFF, 0xFF, 0xFF));
+
+        let linear = rgb!(0f64, 1., 0.).to_linear().to_grayscale();
+        assert!((linear.r - 0.7152).abs() < 1e-12 && linear.r == linear.b, "{:?}", linear);
+        let encoded = rgb!(0f64, 1., 0.).to_grayscale();
+        assert!((encoded.r - Srgb::from_linear(0.7152)).abs() < 1e-12, "{:?}", encoded);
+    }
+
+    #[test]
+    fn test_apca_contrast() {
+        // Reference values from the apca-w3 test suite
+        let expected = [
+            (0x888888, 0xFFFFFF, 63.05647),
+            (0xFFFFFF, 0x888888, -68.54146),
+            (0x000000, 0xAAAAAA, 58.14626),
+            (0xAAAAAA, 0x000000, -56.24113),
+            (0x112233, 0xDDEEFF, 91.66831),
+            (0xDDEEFF, 0x112233, -93.0677),
+        ];
+        for &(text, bg, lc) in expected.iter() {
+            let text = Rgb::<u8>::from_hex(text);
+            let bg = Rgb::<u8>::from_hex(bg);
+            let contrast = apca_contrast(text, bg);
+            assert!((contrast - lc).abs() < 1e-3, "{:?} on {:?}: {} vs {}", text, bg, contrast, lc);
+        }
+        let gray = rgb!(0x80u8, 0x80, 0x80);
+        assert_eq!(apca_contrast(gray, gray), 0.);
     }
 
     #[test]
@@ -0,0 +1,94 @@
+//! Serializes 8 bit colors as `#rrggbb` or `#rrggbbaa` hex strings.
+//!
+//! Use it on fields of type `Rgb<u8>` or `Rgba<u8>` with
+//! `#[serde(with = "color::serde_hex")]`. Both deserialize from the forms
+//! of `Rgb::from_hex_str` and `Rgba::from_hex_str`, short ones included.
+
+use std::fmt::Write;
+use serde::{Serializer, Deserialize, Deserializer, de};
+use rgb::Rgb;
+use alpha::Rgba;
+
+pub trait HexColor: Sized {
+    fn to_hex_string(&self) -> String;
+    fn from_hex_str(hex: &str) -> Option<Self>;
+}
+
+fn write_hex(channels: &[u8]) -> String {
+    let mut hex = String::with_capacity(1 + channels.len() * 2);
+    hex.push('#');
+    for c in channels {
+        write!(hex, "{:02x}", c).unwrap();
+    }
+    hex
+}
+
+impl<S> HexColor for Rgb<u8, S> {
+    fn to_hex_string(&self) -> String {
+        write_hex(&[self.r, self.g, self.b])
+    }
+
+    fn from_hex_str(hex: &str) -> Option<Rgb<u8, S>> {
+        Rgb::from_hex_str(hex).ok()
+    }
+}
+
+impl<S> HexColor for Rgba<u8, S> {
+    fn to_hex_string(&self) -> String {
+        write_hex(&[self.c.r, self.c.g, self.c.b, self.a])
+    }
+
+    fn from_hex_str(hex: &str) -> Option<Rgba<u8, S>> {
+        Rgba::from_hex_str(hex).ok()
+    }
+}
+
+pub fn serialize<C: HexColor, S: Serializer>(color: &C, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&color.to_hex_string())
+}
+
+pub fn deserialize<'de, C: HexColor, D: Deserializer<'de>>(deserializer: D) -> Result<C, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    C::from_hex_str(&hex).ok_or_else(|| de::Error::custom(format!("invalid hex color {}", hex)))
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate serde_json;
+    use {Rgb, Rgba};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Theme {
+        #[serde(with = "::serde_hex")]
+        fg: Rgb<u8>,
+        #[serde(with = "::serde_hex")]
+        bg: Rgba<u8>,
+    }
+
+    #[test]
+    fn test_serde_hex_round_trip() {
+        let theme = Theme {
+            fg: rgb!(0x00, 0x05, 0xFF),
+            bg: rgba!(0xAB, 0xCD, 0xEF, 0x80),
+        };
+        let json = serde_json::to_string(&theme).unwrap();
+        assert_eq!(json, r##"{"fg":"#0005ff","bg":"#abcdef80"}"##);
+        assert_eq!(serde_json::from_str::<Theme>(&json).unwrap(), theme);
+        let upper = r##"{"fg":"#0005FF","bg":"#ABCDEF80"}"##;
+        assert_eq!(serde_json::from_str::<Theme>(upper).unwrap(), theme);
+
+        // Short and alpha-less forms parse like Rgb::from_hex_str
+        let short: Theme = serde_json::from_str(r##"{"fg":"#0af","bg":"#abc8"}"##).unwrap();
+        assert_eq!((short.fg, short.bg), (rgb!(0x00, 0xAA, 0xFF), rgba!(0xAA, 0xBB, 0xCC, 0x88)));
+        let opaque: Theme = serde_json::from_str(r##"{"fg":"#0005ff","bg":"#abcdef"}"##).unwrap();
+        assert_eq!(opaque.bg, rgba!(0xAB, 0xCD, 0xEF, 0xFF));
+    }
+
+    #[test]
+    fn test_serde_hex_invalid() {
+        assert!(serde_json::from_str::<Theme>(r##"{"fg":"#0005f","bg":"#abcdef80"}"##).is_err());
+        assert!(serde_json::from_str::<Theme>(r##"{"fg":"#0005ff","bg":"#abcdef8"}"##).is_err());
+        assert!(serde_json::from_str::<Theme>(r##"{"fg":"#0005ff80","bg":"#abcdef80"}"##).is_err());
+        assert!(serde_json::from_str::<Theme>(r##"{"fg":"#00g5ff","bg":"#abcdef80"}"##).is_err());
+    }
+}
@@ -17,11 +17,14 @@ use channel::Channel;
 use num_traits::Float;
 use rgb::{Rgb, ToRgb};
 use yxy::{Yxy, ToYxy};
-use color_space::{D65, WhitePoint, MatrixColorSpace, Srgb, TransferFunction};
+use color_space::{D65, WhitePoint, MatrixColorSpace, Srgb, TransferFunction, Vec3, Adaptation, adaptation_matrix};
 use num_traits::{zero, NumCast, cast};
 // use srgb::{Srgb, ToSrgb};
 use lab::{Lab, ToLab};
+use std::iter::Sum;
 
+/// CIE XYZ tristimulus values, relative to the white point `Wp` which has a
+/// Y of 1. Use `normalized` for data on the Y = 100 scale.
 #[derive(Clone, Copy, Debug)]
 pub struct Xyz<T = f32, Wp = D65>
 where T: Channel + Float
@@ -43,6 +46,139 @@ impl<T: Channel + Float, Wp: WhitePoint> Xyz<T,Wp> {
     }
 }
 
+impl<T: Channel + Float, Wp: WhitePoint> Xyz<T,Wp> {
+    /// Rescales values on the Y = 100 scale, common in colorimetry tables
+    /// and measurement devices, to the Y = 1 scale this crate uses.
+    pub fn normalized(&self) -> Xyz<T,Wp> {
+        let scale: T = cast(100).unwrap();
+        Xyz::new(self.x / scale, self.y / scale, self.z / scale)
+    }
+
+    /// Bradford chromatic adaptation to the white point `Dst`, e.g. to show
+    /// a D50 measurement on a D65 display.
+    pub fn adapt_to<Dst: WhitePoint>(&self) -> Xyz<T,Dst> {
+        self.adapt_to_with(Adaptation::Bradford)
+    }
+
+    /// Chromatic adaptation to the white point `Dst` with `method`.
+    pub fn adapt_to_with<Dst: WhitePoint>(&self, method: Adaptation) -> Xyz<T,Dst> {
+        let [x, y, z] = (adaptation_matrix::<Wp, Dst, T>(method) * Vec3([self.x, self.y, self.z])).0;
+        Xyz::new(x, y, z)
+    }
+
+    /// Sums both colors as additive light sources.
+    pub fn add_light(&self, other: &Xyz<T,Wp>) -> Xyz<T,Wp> {
+        Xyz::new(self.x + other.x, self.y + other.y, self.z + other.z)
+    }
+}
+
+impl<T: Channel + Float, Wp: WhitePoint> Sum for Xyz<T,Wp> {
+    fn sum<I: Iterator<Item = Xyz<T,Wp>>>(iter: I) -> Xyz<T,Wp> {
+        iter.fold(Xyz::new(zero(), zero(), zero()), |acc, xyz| acc.add_light(&xyz))
+    }
+}
+
+impl<'a, T: Channel + Float, Wp: WhitePoint> Sum<&'a Xyz<T,Wp>> for Xyz<T,Wp> {
+    fn sum<I: Iterator<Item = &'a Xyz<T,Wp>>>(iter: I) -> Xyz<T,Wp> {
+        iter.fold(Xyz::new(zero(), zero(), zero()), |acc, xyz| acc.add_light(xyz))
+    }
+}
+
+// Planckian locus in CIE 1960 uv, Krystek's approximation valid from 1000K to
+// 15000K
+fn planckian_uv(kelvin: f64) -> (f64, f64) {
+    let t = kelvin;
+    let u = (0.860117757 + 1.54118254e-4 * t + 1.28641212e-7 * t * t)
+        / (1. + 8.42420235e-4 * t + 7.08145163e-7 * t * t);
+    let v = (0.317398726 + 4.22806245e-5 * t + 4.20481691e-8 * t * t)
+        / (1. - 2.89741816e-5 * t + 1.61456053e-7 * t * t);
+    (u, v)
+}
+
+// Unit normal to the Planckian locus pointing towards green
+fn planckian_normal(kelvin: f64) -> (f64, f64) {
+    let (u0, v0) = planckian_uv(kelvin - 1.);
+    let (u1, v1) = planckian_uv(kelvin + 1.);
+    let (du, dv) = (u1 - u0, v1 - v0);
+    let len = du.hypot(dv);
+    let (nu, nv) = (-dv / len, du / len);
+    if nv < 0. { (-nu, -nv) } else { (nu, nv) }
+}
+
+impl Xyz<f32, D65> {
+    /// Color of a black body at `kelvin` with unit luminance.
+    pub fn from_temperature(kelvin: f32) -> Xyz<f32, D65> {
+        Xyz::from_temperature_tint(kelvin, 0.)
+    }
+
+    /// Color of a black body at `kelvin` offset perpendicular to the Planckian
+    /// locus by `tint`, the signed distance in CIE 1960 uv (Duv). Positive
+    /// tints shift towards green and negative ones towards magenta.
+    pub fn from_temperature_tint(kelvin: f32, tint: f32) -> Xyz<f32, D65> {
+        let kelvin = kelvin as f64;
+        let (u, v) = planckian_uv(kelvin);
+        let (nu, nv) = planckian_normal(kelvin);
+        let u = u + nu * tint as f64;
+        let v = v + nv * tint as f64;
+        let d = 2. * u - 8. * v + 4.;
+        let x = 3. * u / d;
+        let y = 2. * v / d;
+        Xyz::new((x / y) as f32, 1., ((1. - x - y) / y) as f32)
+    }
+}
+
+impl<T: Channel + Float, Wp: WhitePoint> Xyz<T,Wp> {
+    /// Estimates the correlated color temperature in kelvin and the tint (Duv)
+    /// of a near-neutral color, the inverse of `from_temperature_tint`.
+    ///
+    /// Returns `None` for colors further than 0.05 from the Planckian locus or
+    /// outside of the 1000K to 15000K range, where CCT isn't meaningful.
+    pub fn estimate_temperature_tint(&self) -> Option<(f32, f32)> {
+        const MAX_TINT: f64 = 0.05;
+        let x = self.x.to_channel_f64();
+        let y = self.y.to_channel_f64();
+        let z = self.z.to_channel_f64();
+        let d = x + 15. * y + 3. * z;
+        if d <= 0. {
+            return None;
+        }
+        let (u, v) = (4. * x / d, 6. * y / d);
+        let distance = |mired: f64| {
+            let (pu, pv) = planckian_uv(1e6 / mired);
+            (u - pu).hypot(v - pv)
+        };
+
+        // Coarse scan in mireds, which are close to perceptually uniform,
+        // then refine around the closest sample
+        let (min_mired, max_mired) = (1e6 / 15000., 1e6 / 1000.);
+        let mut best = min_mired;
+        let mut mired = min_mired;
+        while mired <= max_mired {
+            if distance(mired) < distance(best) {
+                best = mired;
+            }
+            mired += 1.;
+        }
+        let mut lo = (best - 1.).max(min_mired);
+        let mut hi = (best + 1.).min(max_mired);
+        for _ in 0..50 {
+            let m1 = lo + (hi - lo) / 3.;
+            let m2 = hi - (hi - lo) / 3.;
+            if distance(m1) < distance(m2) { hi = m2 } else { lo = m1 }
+        }
+        let kelvin = 1e6 / ((lo + hi) / 2.);
+
+        let (pu, pv) = planckian_uv(kelvin);
+        let (nu, nv) = planckian_normal(kelvin);
+        let tint = (u - pu) * nu + (v - pv) * nv;
+        // The closest point sits on the ends of the range for colors beyond it
+        if tint.abs() > MAX_TINT || !(1000.5..14999.5).contains(&kelvin) {
+            return None;
+        }
+        Some((kelvin as f32, tint as f32))
+    }
+}
+
 pub trait ToXyz {
     type WhitePoint: WhitePoint;
     fn to_xyz<T: Channel + Float + std::fmt::Debug>(&self) -> Xyz<T, Self::WhitePoint>;
@@ -97,4 +233,115 @@ impl<T: Channel + Float, Wp: WhitePoint> ToYxy for Xyz<T, Wp> {
         }
         Yxy{x: x.to_channel(), y: y.to_channel(), luma: luma.to_channel(), white_point: Wp::default()}
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::Xyz;
+    use yxy::ToYxy;
+    use rgb::{Rgb, ToRgb};
+    use color_space::{D50, D65, WhitePoint, Adaptation};
+    use lab::{Lab, ToLab};
+    use super::ToXyz;
+
+    #[test]
+    fn test_sum() {
+        let samples = [
+            Xyz::<f32, D65>::new(0.1, 0.2, 0.3),
+            Xyz::new(0.05, 0.5, 0.),
+            Xyz::new(0.4, 0.1, 0.25),
+        ];
+        let expected = samples.iter().fold((0., 0., 0.), |(x, y, z), s| (x + s.x, y + s.y, z + s.z));
+        let sum = samples.iter().copied().sum::<Xyz<f32, D65>>();
+        assert_eq!((sum.x, sum.y, sum.z), expected);
+        let sum: Xyz<f32, D65> = samples.iter().sum();
+        assert_eq!((sum.x, sum.y, sum.z), expected);
+        let empty: Xyz<f32, D65> = samples[..0].iter().sum();
+        assert_eq!((empty.x, empty.y, empty.z), (0., 0., 0.));
+    }
+
+    #[test]
+    fn test_white_convention() {
+        let white = D65::xyz::<f64>();
+        assert_eq!(white.y, 1.);
+        let white = Xyz::<f64, D65>::new(white.x, white.y, white.z);
+        let lab: Lab<f64, D65> = white.to_lab();
+        assert!((lab.l - 100.).abs() < 1e-9 && lab.a.abs() < 1e-9 && lab.b.abs() < 1e-9, "{:?}", lab);
+        assert_eq!(D50::xyz::<f64>().y, 1.);
+
+        let measured = Xyz::<f64, D65>::new(95.047, 100., 108.883).normalized();
+        assert!((measured.x - white.x).abs() < 1e-12, "{:?}", measured);
+        assert!((measured.y - 1.).abs() < 1e-12, "{:?}", measured);
+        assert!((measured.z - white.z).abs() < 1e-12, "{:?}", measured);
+    }
+
+    #[test]
+    fn test_adapt_to() {
+        let d50 = D50::xyz::<f64>();
+        let d65 = D65::xyz::<f64>();
+        let adapted: Xyz<f64, D65> = Xyz::<f64, D50>::new(d50.x, d50.y, d50.z).adapt_to();
+        assert!((adapted.x - d65.x).abs() < 1e-9 && (adapted.y - d65.y).abs() < 1e-9 && (adapted.z - d65.z).abs() < 1e-9, "{:?}", adapted);
+
+        let xyz = Xyz::<f64, D65>::new(0.3, 0.4, 0.2);
+        let back = xyz.adapt_to::<D50>().adapt_to::<D65>();
+        assert!((back.x - xyz.x).abs() < 1e-6 && (back.y - xyz.y).abs() < 1e-6 && (back.z - xyz.z).abs() < 1e-6, "{:?}", back);
+    }
+
+    #[test]
+    fn test_adapt_to_with() {
+        let d50 = D50::xyz::<f64>();
+        let d65 = D65::xyz::<f64>();
+        for &method in &[Adaptation::VonKries, Adaptation::Bradford, Adaptation::Cat02] {
+            let adapted: Xyz<f64, D65> = Xyz::<f64, D50>::new(d50.x, d50.y, d50.z).adapt_to_with(method);
+            assert!((adapted.x - d65.x).abs() < 1e-9 && (adapted.y - d65.y).abs() < 1e-9 && (adapted.z - d65.z).abs() < 1e-9, "{:?} {:?}", method, adapted);
+        }
+
+        let xyz = Xyz::<f64, D50>::new(0.3, 0.4, 0.2);
+        let bradford = xyz.adapt_to_with::<D65>(Adaptation::Bradford);
+        let default = xyz.adapt_to::<D65>();
+        assert!(bradford.x == default.x && bradford.y == default.y && bradford.z == default.z);
+        let von_kries = xyz.adapt_to_with::<D65>(Adaptation::VonKries);
+        assert!((von_kries.x - default.x).abs() > 1e-4, "{:?}", von_kries);
+    }
+
+    #[test]
+    fn test_from_temperature() {
+        let xyy = Xyz::from_temperature(6504.).to_yxy::<f32>();
+        assert!((xyy.x - 0.3135).abs() < 0.0005, "{}", xyy.x);
+        assert!((xyy.y - 0.3237).abs() < 0.0005, "{}", xyy.y);
+        let warm: Rgb<f32> = Xyz::from_temperature(2700.).to_rgb();
+        let cold: Rgb<f32> = Xyz::from_temperature(10000.).to_rgb();
+        assert!(warm.r > warm.b && cold.b > cold.r);
+    }
+
+    #[test]
+    fn test_estimate_temperature_tint() {
+        let white = Rgb::<u8>::new(0xFF, 0xFF, 0xFF).to_xyz::<f32>();
+        let (kelvin, tint) = white.estimate_temperature_tint().unwrap();
+        assert!((kelvin - 6504.).abs() < 10., "{}", kelvin);
+        assert!(tint.abs() < 0.005, "{}", tint);
+
+        for &(k, t) in [(2700., 0.), (4000., 0.01), (9000., -0.02)].iter() {
+            let (kelvin, tint) = Xyz::from_temperature_tint(k, t).estimate_temperature_tint().unwrap();
+            assert!((kelvin - k).abs() < k * 0.001, "{} {}", k, kelvin);
+            assert!((tint - t).abs() < 1e-4, "{} {}", t, tint);
+        }
+
+        assert!(Rgb::<u8>::new(0, 0xFF, 0).to_xyz::<f32>().estimate_temperature_tint().is_none());
+        assert!(Rgb::<u8>::new(0, 0, 0xFF).to_xyz::<f32>().estimate_temperature_tint().is_none());
+    }
+
+    #[test]
+    fn test_from_temperature_tint() {
+        let cct = Xyz::from_temperature(5000.);
+        let tint0 = Xyz::from_temperature_tint(5000., 0.);
+        assert_eq!((cct.x, cct.y, cct.z), (tint0.x, tint0.y, tint0.z));
+
+        let green: Rgb<f32> = Xyz::from_temperature_tint(5000., 0.02).to_rgb();
+        let magenta: Rgb<f32> = Xyz::from_temperature_tint(5000., -0.02).to_rgb();
+        let neutral: Rgb<f32> = cct.to_rgb();
+        assert!(green.g / green.r > neutral.g / neutral.r);
+        assert!(green.g / green.b > neutral.g / neutral.b);
+        assert!(magenta.g / magenta.r < neutral.g / neutral.r);
+        assert!(magenta.g / magenta.b < neutral.g / neutral.b);
+    }
+}
@@ -57,7 +57,7 @@ where
             x: self.x + other.x,
             y: self.y + other.y,
             luma: self.luma + other.luma,
-            white_point: Wp::default(),
+            white_point: self.white_point,
         }
     }
 }